@@ -0,0 +1,166 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::Result;
+use clap::Args;
+use subtale_mimir::prelude::*;
+
+use crate::{
+    load::{self, LoadedRuleset},
+    plural,
+};
+
+/// Compares two versions of a ruleset.
+#[derive(Args)]
+pub struct DiffArgs {
+    /// The old version of the ruleset (JSON, or RON with a `.ron` extension).
+    old: PathBuf,
+    /// The new version of the ruleset.
+    new: PathBuf,
+    /// Exit with a non-zero status if the rulesets differ.
+    #[arg(long)]
+    exit_code: bool,
+}
+
+/// Describes the differences between two rulesets, one line per change.
+///
+/// Unnamed rules are identified by their position in the file that contains
+/// them (the old ruleset for removed rules, and the new ruleset otherwise).
+fn describe(old: &LoadedRuleset, new: &LoadedRuleset) -> Vec<String> {
+    let diff = diff_rulesets(&old.ruleset, &new.ruleset);
+    let mut lines = Vec::new();
+
+    for &index in &diff.removed {
+        lines.push(format!("- {}", old.rule_id(index)));
+    }
+
+    for &index in &diff.added {
+        let rule = &new.ruleset.rules()[index];
+        let criteria = rule.evaluators.len();
+
+        lines.push(format!(
+            "+ {} ({} {}) => {}",
+            new.rule_id(index),
+            criteria,
+            plural(criteria, "criterion", "criteria"),
+            load::outcome(&rule.outcome)
+        ));
+    }
+
+    for change in &diff.changed {
+        let id = new.rule_id(change.new);
+
+        let mut moves = Vec::new();
+
+        if let Some((before, after)) = change.specificity {
+            moves.push(format!("specificity {} -> {}", before, after));
+        }
+
+        if let Some((before, after)) = change.tier {
+            moves.push(format!("tier {} -> {}", before, after));
+        }
+
+        lines.push(
+            if moves.is_empty() {
+                format!("~ {}", id)
+            } else {
+                format!("~ {} ({})", id, moves.join(", "))
+            },
+        );
+
+        for criterion in &change.criteria {
+            lines.push(match criterion {
+                CriterionChange::Added { fact, evaluator } => {
+                    format!("    + {}: {}", fact, load::evaluator(evaluator))
+                },
+                CriterionChange::Removed { fact, evaluator } => {
+                    format!("    - {}: {}", fact, load::evaluator(evaluator))
+                },
+                CriterionChange::Changed { fact, old, new } => format!(
+                    "    ~ {}: {} -> {}",
+                    fact,
+                    load::evaluator(old),
+                    load::evaluator(new)
+                ),
+            });
+        }
+
+        if let Some((before, after)) = change.outcome {
+            lines.push(format!(
+                "    outcome: {} -> {}",
+                load::outcome(before),
+                load::outcome(after)
+            ));
+        }
+    }
+
+    lines
+}
+
+pub fn run(args: DiffArgs) -> Result<ExitCode> {
+    let old = load::ruleset(&args.old)?;
+    let new = load::ruleset(&args.new)?;
+
+    let lines = describe(&old, &new);
+
+    for line in &lines {
+        println!("{}", line);
+    }
+
+    if lines.is_empty() {
+        println!("the rulesets are the same");
+        Ok(ExitCode::SUCCESS)
+    } else if args.exit_code {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_diff() {
+        let old = LoadedRuleset::from(Ruleset::new(vec![
+            load::rule("hurt", "Ouch", &[("health", FloatEvaluator::lt(0.5))]),
+            load::rule("tired", "Phew", &[("stamina", FloatEvaluator::lt(0.5))]),
+            load::rule(
+                "greet",
+                "Hello",
+                &[("concept", FloatEvaluator::EqualTo(1.))],
+            ),
+        ]));
+        let new = LoadedRuleset::from(Ruleset::new(vec![
+            load::rule(
+                "hurt",
+                "Ow",
+                &[
+                    ("health", FloatEvaluator::lt(0.25)),
+                    ("enemies", FloatEvaluator::gt(0.)),
+                ],
+            ),
+            load::rule("bored", "Yawn", &[("stamina", FloatEvaluator::gt(0.9))]),
+            load::rule(
+                "greet",
+                "Hello",
+                &[("concept", FloatEvaluator::EqualTo(1.))],
+            ),
+        ]));
+
+        assert_eq!(
+            describe(&old, &new),
+            [
+                "- tired",
+                "+ bored (1 criterion) => Yawn",
+                "~ hurt (specificity 1 -> 2)",
+                r#"    ~ health: {"lt":0.5} -> {"lt":0.25}"#,
+                r#"    + enemies: {"gt":0.0}"#,
+                "    outcome: Ouch -> Ow",
+                // "greet" is unchanged, but "hurt" is now more specific
+                "~ greet (tier 0 -> 1)",
+            ]
+        );
+        assert!(describe(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::Result;
+use clap::Args;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::load::{self, FloatRule, LoadedRuleset};
+
+/// Evaluates a query against a ruleset, printing the matched rules and the
+/// selected outcome.
+#[derive(Args)]
+pub struct EvalArgs {
+    /// The ruleset file (JSON, or RON with a `.ron` extension).
+    ruleset: PathBuf,
+    /// The query file: a map of fact keys to values (JSON or RON).
+    query: PathBuf,
+    /// Seeds the random number generator used to break ties between equally
+    /// specific rules (the same generator as seeded scenarios, so a seed
+    /// always selects the same rule).
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Explains why each rule did (or didn't) match the query.
+    #[arg(long)]
+    explain: bool,
+}
+
+pub fn run(args: EvalArgs) -> Result<ExitCode> {
+    let loaded = load::ruleset(&args.ruleset)?;
+    let ruleset = &loaded.ruleset;
+    let query = load::query(&args.query)?;
+
+    let matched = ruleset.evaluate_all(&query);
+
+    if let Some(first) = matched.first() {
+        println!(
+            "matched {} {} with {} {}:",
+            matched.len(),
+            crate::plural(matched.len(), "rule", "rules"),
+            first.evaluators.len(),
+            crate::plural(first.evaluators.len(), "criterion", "criteria")
+        );
+
+        for rule in &matched {
+            println!("  {}", describe(&loaded, rule));
+        }
+
+        let selected = match args.seed {
+            Some(seed) => ruleset.evaluate_with_rng(&query, &mut ChaCha8Rng::seed_from_u64(seed)),
+            None => ruleset.evaluate(&query),
+        };
+
+        if let Some(rule) = selected {
+            println!("selected: {}", describe(&loaded, rule));
+        }
+    } else {
+        println!("no rules matched");
+    }
+
+    if args.explain {
+        println!();
+        println!("rules:");
+
+        for (index, rule) in ruleset.rules().iter().enumerate() {
+            let failures: Vec<_> = rule.failures(&query).iter().map(load::failure).collect();
+            let status = if failures.is_empty() {
+                "[match]"
+            } else {
+                "[fail]"
+            };
+
+            print!(
+                "  {:<7} {} ({} {})",
+                status,
+                loaded.rule_id(index),
+                rule.evaluators.len(),
+                crate::plural(rule.evaluators.len(), "criterion", "criteria")
+            );
+
+            if failures.is_empty() {
+                println!();
+            } else {
+                println!(": {}", failures.join("; "));
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Describes a rule (by its identifier and outcome).
+fn describe(loaded: &LoadedRuleset, rule: &FloatRule) -> String {
+    let index = loaded
+        .ruleset
+        .rules()
+        .iter()
+        .position(|x| core::ptr::eq(x, rule))
+        .unwrap_or_default();
+
+    format!(
+        "{} => {}",
+        loaded.rule_id(index),
+        load::outcome(&rule.outcome)
+    )
+}
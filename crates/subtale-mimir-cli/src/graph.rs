@@ -0,0 +1,69 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use subtale_mimir::prelude::*;
+
+use crate::load;
+
+/// Exports a graph of a ruleset.
+#[derive(Args)]
+pub struct GraphArgs {
+    /// The ruleset file (JSON, or RON with a `.ron` extension).
+    ruleset: PathBuf,
+    /// The format of the graph.
+    #[arg(long, value_enum, default_value_t = Format::Dot)]
+    format: Format,
+}
+
+/// A format that graphs can be exported in.
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Graphviz's DOT language.
+    Dot,
+    /// JSON (a list of rules, and pairs of rule indices that could tie or
+    /// subsume each other).
+    Json,
+}
+
+/// Exports the graph of a ruleset in the provided format.
+fn export(graph: &RuleGraph<String>, format: Format) -> Result<String> {
+    Ok(match format {
+        Format::Dot => graph.to_dot(),
+        Format::Json => serde_json::to_string_pretty(graph)? + "\n",
+    })
+}
+
+pub fn run(args: GraphArgs) -> Result<ExitCode> {
+    let ruleset = load::ruleset(&args.ruleset)?.ruleset;
+    let graph = RuleGraph::new(&ruleset);
+
+    print!("{}", export(&graph, args.format)?);
+
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn export_graph() {
+        let mut hurt = Rule::named("hurt", json!("Ouch"));
+        hurt.insert("health".to_string(), FloatEvaluator::lt(0.5));
+        let mut badly_hurt = Rule::named("badly \"hurt\"", json!("Argh"));
+        badly_hurt.insert("health".to_string(), FloatEvaluator::lt(0.25));
+
+        let ruleset = Ruleset::new(vec![hurt, badly_hurt]);
+        let graph = RuleGraph::new(&ruleset);
+
+        let dot = export(&graph, Format::Dot).unwrap();
+        assert!(dot.contains(r#"[label="badly \"hurt\""];"#));
+
+        let json: Value = serde_json::from_str(&export(&graph, Format::Json).unwrap()).unwrap();
+        assert_eq!(json["rules"][0]["facts"], json!(["health"]));
+        assert_eq!(json["ties"], json!([[0, 1]]));
+    }
+}
@@ -0,0 +1,270 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::Result;
+use clap::Args;
+use subtale_mimir::prelude::*;
+
+use crate::load::{self, LoadedRuleset};
+
+/// Lints ruleset files, reporting problems such as duplicate rules and
+/// criteria that can never be satisfied.
+#[derive(Args)]
+pub struct LintArgs {
+    /// The ruleset files to lint (JSON, or RON with a `.ron` extension).
+    #[arg(required = true)]
+    rulesets: Vec<PathBuf>,
+    /// A schema file (a map of fact keys to value types) used to report
+    /// unknown fact keys.
+    #[arg(long)]
+    schema: Option<PathBuf>,
+    /// Exits with a non-zero status if any warnings are reported (as well as
+    /// errors).
+    #[arg(long)]
+    deny_warnings: bool,
+    /// Assumes that queries always contain every fact checked by the
+    /// rulesets, and warns about rules that are never chosen as a result.
+    #[arg(long)]
+    complete_snapshots: bool,
+}
+
+/// A problem found in a ruleset file.
+pub struct Finding {
+    /// `true` if the finding is an error (rather than a warning).
+    pub error: bool,
+    /// The identifier of the rule that the finding refers to.
+    pub rule: String,
+    /// A description of the finding.
+    pub message: String,
+}
+
+/// Lints the provided ruleset, returning each problem that it contains (in the
+/// order of the rules in the file).
+pub fn findings(
+    loaded: &LoadedRuleset,
+    schema: Option<&FactSchema<String>>,
+    options: LintOptions,
+) -> Vec<Finding> {
+    let ruleset = &loaded.ruleset;
+
+    // Lints and schema errors refer to rules by their position in the file
+    let mut indices = vec![0; ruleset.rules().len()];
+    for index in 0..indices.len() {
+        indices[ruleset.position(index)] = index;
+    }
+
+    let id = |position: usize| loaded.rule_id(indices[position]);
+
+    let lints = lint_ruleset_with(ruleset, options).into_iter().map(|lint| {
+        let (error, message) = match &lint {
+            // Fallback rules are often intentionally empty
+            Lint::EmptyRule { .. } => (
+                false,
+                "the rule has no criteria, so it matches every query".to_string(),
+            ),
+            Lint::ImpossibleEvaluator { fact, .. } => (
+                true,
+                format!("the criterion for {} can never be satisfied", fact),
+            ),
+            Lint::Duplicate { of, .. } => (true, format!("duplicate of {}", id(*of))),
+            Lint::ShadowedInCompleteSnapshots { by, .. } => (
+                false,
+                format!(
+                    "shadowed by {} whenever the query contains every fact they check",
+                    by.iter().map(|&by| id(by)).collect::<Vec<_>>().join(", ")
+                ),
+            ),
+        };
+
+        (
+            lint.rule(),
+            Finding {
+                error,
+                rule: id(lint.rule()),
+                message,
+            },
+        )
+    });
+
+    let schema_errors = schema
+        .map(|schema| schema.validate_ruleset(ruleset))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|error| {
+            let message = match error.kind {
+                SchemaErrorKind::UnknownKey => format!("unknown fact {}", error.key),
+                SchemaErrorKind::TypeMismatch { expected, found } => format!(
+                    "fact {} should be a {} (found {})",
+                    error.key, expected, found
+                ),
+            };
+
+            // Schema errors always refer to a rule when validating a ruleset
+            let rule = error.rule.unwrap_or_default();
+
+            (
+                rule,
+                Finding {
+                    error: true,
+                    rule: id(rule),
+                    message,
+                },
+            )
+        });
+
+    let mut findings: Vec<_> = lints.chain(schema_errors).collect();
+    findings.sort_by_key(|(rule, _)| *rule);
+    findings.into_iter().map(|(_, finding)| finding).collect()
+}
+
+pub fn run(args: LintArgs) -> Result<ExitCode> {
+    let schema: Option<FactSchema<String>> = args.schema.as_deref().map(load::parse).transpose()?;
+    let options = LintOptions {
+        complete_snapshots: args.complete_snapshots,
+    };
+
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for path in &args.rulesets {
+        let ruleset = load::ruleset(path)?;
+
+        for finding in findings(&ruleset, schema.as_ref(), options) {
+            let level = if finding.error {
+                errors += 1;
+                "error"
+            } else {
+                warnings += 1;
+                "warning"
+            };
+
+            println!(
+                "{}: {}: {}: {}",
+                level,
+                path.display(),
+                finding.rule,
+                finding.message
+            );
+        }
+    }
+
+    println!(
+        "{} {}, {} {} in {} {}",
+        errors,
+        crate::plural(errors, "error", "errors"),
+        warnings,
+        crate::plural(warnings, "warning", "warnings"),
+        args.rulesets.len(),
+        crate::plural(args.rulesets.len(), "file", "files")
+    );
+
+    if errors > 0 || (args.deny_warnings && warnings > 0) {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lint_findings() {
+        let ruleset = LoadedRuleset::from(Ruleset::new(vec![
+            load::rule(
+                "hurt",
+                "hurt",
+                &[("health", FloatEvaluator::range(50., 25.))],
+            ),
+            load::rule("typo", "typo", &[("helth", FloatEvaluator::lt(25.))]),
+            load::rule("fallback", "fallback", &[]),
+        ]));
+
+        let mut schema = FactSchema::new();
+        schema.insert("health".to_string(), ValueType::Number);
+
+        let findings = findings(&ruleset, Some(&schema), LintOptions::default());
+        let messages: Vec<_> = findings
+            .iter()
+            .map(|finding| format!("{}: {}", finding.rule, finding.message))
+            .collect();
+
+        assert_eq!(messages.len(), 3);
+        assert!(findings[..2].iter().all(|finding| finding.error));
+        assert!(messages.contains(&"hurt: the criterion for health can never be satisfied".into()));
+        assert!(messages.contains(&"typo: unknown fact helth".into()));
+        assert_eq!(
+            messages[2],
+            "fallback: the rule has no criteria, so it matches every query"
+        );
+        assert!(!findings[2].error);
+    }
+
+    #[test]
+    fn unnamed_findings() {
+        let hurt = || {
+            let mut hurt = Rule::new(serde_json::json!("Ouch"));
+            hurt.insert("health".to_string(), FloatEvaluator::lt(25.));
+            hurt
+        };
+
+        let ruleset = LoadedRuleset::from(Ruleset::new(vec![
+            Rule::new(serde_json::json!("Hmm")),
+            hurt(),
+            hurt(),
+        ]));
+
+        // Unnamed rules are identified (and sorted) by their position in the
+        // file, rather than their index in the ruleset
+        let messages: Vec<_> = findings(&ruleset, None, LintOptions::default())
+            .iter()
+            .map(|finding| format!("{}: {}", finding.rule, finding.message))
+            .collect();
+
+        assert_eq!(
+            messages,
+            [
+                "#0: the rule has no criteria, so it matches every query",
+                "#2: duplicate of #1"
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_snapshot_findings() {
+        let ruleset = LoadedRuleset::from(Ruleset::new(vec![
+            load::rule("hurt", "hurt", &[("health", FloatEvaluator::lt(25.))]),
+            load::rule(
+                "unarmored",
+                "unarmored",
+                &[
+                    ("health", FloatEvaluator::lt(50.)),
+                    ("armor", FloatEvaluator::lt(10.)),
+                ],
+            ),
+            load::rule(
+                "armored",
+                "armored",
+                &[
+                    ("health", FloatEvaluator::lt(50.)),
+                    ("armor", FloatEvaluator::gte(10.)),
+                ],
+            ),
+        ]));
+
+        // "hurt" is chosen for queries without an armor fact
+        assert!(findings(&ruleset, None, LintOptions::default()).is_empty());
+
+        let options = LintOptions {
+            complete_snapshots: true,
+        };
+        let findings = findings(&ruleset, None, options);
+
+        assert_eq!(findings.len(), 1);
+        assert!(!findings[0].error);
+        assert_eq!(findings[0].rule, "hurt");
+        assert!(findings[0]
+            .message
+            .ends_with("whenever the query contains every fact they check"));
+    }
+}
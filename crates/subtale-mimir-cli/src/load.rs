@@ -0,0 +1,240 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use subtale_mimir::{
+    csv::{self, CsvColumns},
+    file::{Migrations, RulesetFile},
+    prelude::*,
+};
+
+/// The kind of ruleset loaded by the command-line tool: facts are numbers
+/// (compared with `FloatEvaluator`) and outcomes can be any value (e.g. a line
+/// of dialogue, or a map describing an event).
+pub type FloatRuleset = Ruleset<String, f64, FloatEvaluator, Value>;
+
+/// The kind of rule stored in a `FloatRuleset`.
+pub type FloatRule = Rule<String, f64, FloatEvaluator, Value>;
+
+/// A ruleset loaded from a file, along with where each of its rules was
+/// defined in the file (used to identify unnamed rules).
+pub struct LoadedRuleset {
+    pub ruleset: FloatRuleset,
+    /// The row that each rule was read from (in the order of the rules in the
+    /// file), if the ruleset was loaded from CSV.
+    rows: Option<Vec<usize>>,
+}
+
+impl LoadedRuleset {
+    /// Returns the identifier of the rule at the provided index (in
+    /// `Ruleset::rules`) in the output of the command-line tool: its name, or
+    /// its position in the file if it's unnamed (e.g. `#3` for the fourth rule
+    /// in a list of rules, or `row 4` for a rule read from CSV).
+    pub fn rule_id(&self, index: usize) -> String {
+        let position = self.ruleset.position(index);
+
+        match (&self.ruleset.rules()[index].name, &self.rows) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rows)) => format!("row {}", rows[position]),
+            (None, None) => format!("#{}", position),
+        }
+    }
+}
+
+impl From<FloatRuleset> for LoadedRuleset {
+    fn from(ruleset: FloatRuleset) -> Self {
+        Self {
+            ruleset,
+            rows: None,
+        }
+    }
+}
+
+/// Parses the contents of the file at the provided path, as RON if the file
+/// has a `.ron` extension (or as JSON otherwise).
+pub fn parse<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("couldn't read {}", path.display()))?;
+
+    let parsed = if path.extension().is_some_and(|extension| extension == "ron") {
+        ron::from_str(&text).map_err(anyhow::Error::from)
+    } else {
+        serde_json::from_str(&text).map_err(anyhow::Error::from)
+    };
+
+    parsed.with_context(|| format!("couldn't parse {}", path.display()))
+}
+
+/// Loads a ruleset from a ruleset file (or a plain list of rules), migrating
+/// files written with older format versions, or from CSV if the file has a
+/// `.csv` extension (with `name` and `outcome` columns).
+pub fn ruleset(path: &Path) -> Result<LoadedRuleset> {
+    if path.extension().is_some_and(|extension| extension == "csv") {
+        return csv_ruleset(path);
+    }
+
+    let file = RulesetFile::from_json_value(parse(path)?, &Migrations::default())
+        .with_context(|| format!("couldn't load ruleset from {}", path.display()))?;
+
+    Ok(file.into_ruleset().into())
+}
+
+/// Loads a ruleset from CSV, with each outcome as a string.
+fn csv_ruleset(path: &Path) -> Result<LoadedRuleset> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("couldn't read {}", path.display()))?;
+
+    let rules = csv::parse_rules(&text, &CsvColumns::default())
+        .with_context(|| format!("couldn't load ruleset from {}", path.display()))?;
+
+    let (rows, rules) = rules
+        .into_iter()
+        .map(|(row, rule)| {
+            let mut loaded = Rule::new(Value::String(rule.outcome));
+            loaded.name = rule.name;
+            loaded.evaluators = rule.evaluators;
+            (row, loaded)
+        })
+        .unzip();
+
+    Ok(LoadedRuleset {
+        ruleset: Ruleset::new(rules),
+        rows: Some(rows),
+    })
+}
+
+/// Loads a query from a file containing a map of fact keys to values.
+pub fn query(path: &Path) -> Result<Query<String, f64>> {
+    let facts: IndexMap<String, f64> = parse(path)?;
+    let mut query = Query::with_capacity(facts.len());
+
+    for (fact, value) in facts {
+        query.insert(fact, value);
+    }
+
+    Ok(query)
+}
+
+/// Formats an outcome for display (strings are displayed without quotes).
+pub fn outcome(outcome: &Value) -> String {
+    match outcome {
+        Value::String(outcome) => outcome.clone(),
+        outcome => outcome.to_string(),
+    }
+}
+
+/// Formats an evaluator for display (using its serialized representation,
+/// e.g. `{"gte":5.0}`).
+pub fn evaluator(evaluator: &FloatEvaluator) -> String {
+    serde_json::to_string(evaluator).unwrap_or_else(|_| format!("{:?}", evaluator))
+}
+
+/// Describes why a criterion failed (e.g. `health = 0.7 fails {"lt":0.5}`).
+pub fn failure(failure: &CriterionFailure<String, f64, FloatEvaluator>) -> String {
+    match failure.value {
+        Some(value) => format!(
+            "{} = {} fails {}",
+            failure.fact,
+            value,
+            evaluator(failure.evaluator)
+        ),
+        None => format!("{} is missing", failure.fact),
+    }
+}
+
+/// Creates a named rule with the provided outcome and criteria (for tests).
+#[cfg(test)]
+pub fn rule(name: &str, outcome: &str, criteria: &[(&str, FloatEvaluator)]) -> FloatRule {
+    let mut rule = Rule::named(name, serde_json::json!(outcome));
+    for (fact, evaluator) in criteria {
+        rule.insert(fact.to_string(), *evaluator);
+    }
+    rule
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn write(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("mimir-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_ruleset() {
+        let json = write(
+            "rules.json",
+            r#"[{"name": "hurt", "criteria": {"health": {"lt": 0.5}}, "outcome": "Ouch"}]"#,
+        );
+        let ron = write(
+            "rules.ron",
+            r#"(version: 1, keys: ["health"], rules: [(criteria: {"health": {"lt": 0.5}}, outcome: "Ouch")])"#,
+        );
+
+        let csv = write("rules.csv", "name,health,outcome\nhurt,<0.5,Ouch\n");
+
+        for path in [json, ron, csv] {
+            let ruleset = ruleset(&path).unwrap().ruleset;
+            assert_eq!(ruleset.rules().len(), 1);
+            assert_eq!(
+                ruleset.rules()[0].evaluators["health"],
+                FloatEvaluator::lt(0.5)
+            );
+            assert_eq!(outcome(&ruleset.rules()[0].outcome), "Ouch");
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn unnamed_rule_ids() {
+        let json = write(
+            "unnamed.json",
+            r#"[
+                {"criteria": {}, "outcome": "Hello"},
+                {"name": "hurt", "criteria": {"health": {"lt": 0.5}}, "outcome": "Ouch"},
+                {"criteria": {"health": {"lt": 0.25}}, "outcome": "Argh"}
+            ]"#,
+        );
+        let csv = write(
+            "unnamed.csv",
+            "name,health,outcome
+,,Hello
+hurt,<0.5,Ouch
+
+,<0.25,Argh
+",
+        );
+
+        // Unnamed rules are identified by where they're defined in the file,
+        // rather than their index in the (sorted) ruleset
+        for (path, ids) in [
+            (json, ["hurt", "#2", "#0"]),
+            (csv, ["hurt", "row 5", "row 2"]),
+        ] {
+            let loaded = ruleset(&path).unwrap();
+            let rules = loaded.ruleset.rules();
+            assert_eq!(outcome(&rules[0].outcome), "Ouch");
+            assert_eq!(outcome(&rules[1].outcome), "Argh");
+
+            let found: Vec<_> = (0..rules.len())
+                .map(|index| loaded.rule_id(index))
+                .collect();
+            assert_eq!(found, ids);
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn load_query() {
+        let path = write("query.ron", r#"{"health": 0.25, "enemies": 3}"#);
+        let query = query(&path).unwrap();
+        assert_eq!(query.facts()["enemies"], 3.);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+//! `mimir` is a command-line tool for testing Mímir rulesets without running
+//! your game.
+
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+/// Compares versions of rulesets.
+mod diff;
+/// Evaluates queries against rulesets.
+mod eval;
+/// Exports graphs of rulesets.
+mod graph;
+/// Lints rulesets.
+mod lint;
+/// Loads rulesets and queries from files.
+mod load;
+/// Explores rulesets interactively.
+mod repl;
+/// Runs scenarios against rulesets.
+mod test;
+
+#[derive(Parser)]
+#[command(name = "mimir", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compares two versions of a ruleset, listing the rules that were added,
+    /// removed or changed.
+    Diff(diff::DiffArgs),
+    /// Evaluates a query against a ruleset.
+    Eval(eval::EvalArgs),
+    /// Exports a graph of the facts used by a ruleset's rules, and the rules
+    /// that could tie or subsume each other.
+    Graph(graph::GraphArgs),
+    /// Lints ruleset files, exiting with a non-zero status if any errors are
+    /// found.
+    Lint(lint::LintArgs),
+    /// Explores a ruleset interactively.
+    Repl(repl::ReplArgs),
+    /// Runs scenario files against a ruleset, exiting with a non-zero status
+    /// if any scenarios fail.
+    Test(test::TestArgs),
+}
+
+fn main() -> Result<ExitCode> {
+    match Cli::parse().command {
+        Command::Diff(args) => diff::run(args),
+        Command::Eval(args) => eval::run(args),
+        Command::Graph(args) => graph::run(args),
+        Command::Lint(args) => lint::run(args),
+        Command::Repl(args) => repl::run(args),
+        Command::Test(args) => test::run(args),
+    }
+}
+
+/// Returns the singular or plural form of a word, depending on the count.
+fn plural<'a>(count: usize, singular: &'a str, plural: &'a str) -> &'a str {
+    if count == 1 {
+        singular
+    } else {
+        plural
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use anyhow::Result;
+use clap::Args;
+use subtale_mimir::prelude::*;
+
+use crate::load::{self, LoadedRuleset};
+
+/// Explores a ruleset interactively, by changing the facts in a query and
+/// printing the rules that match (or almost match) after each change.
+#[derive(Args)]
+pub struct ReplArgs {
+    /// The ruleset file (JSON, or RON with a `.ron` extension).
+    ruleset: PathBuf,
+}
+
+const HELP: &str = "\
+commands:
+  set <fact> <value>  sets the value of a fact
+  unset <fact>        removes a fact
+  clear               removes every fact
+  facts               prints the current facts
+  help                prints this message
+  quit                exits the REPL";
+
+/// The state of a REPL session: the ruleset being explored, and the query
+/// built up by the user's commands.
+struct Session {
+    loaded: LoadedRuleset,
+    query: Query<String, f64>,
+}
+
+impl Session {
+    /// Handles a line of input, writing the response to `out`.
+    ///
+    /// Returns `false` if the session should end.
+    fn handle(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let words: Vec<_> = line.split_whitespace().collect();
+
+        match words[..] {
+            [] => {},
+            ["set", fact, value] => match value.parse::<f64>() {
+                Ok(value) => {
+                    self.query.insert(fact.to_string(), value);
+                    self.report(out)?;
+                },
+                Err(_) => writeln!(out, "expected a number, found {}", value)?,
+            },
+            ["unset", fact] => {
+                if self.query.remove(fact).is_none() {
+                    writeln!(out, "{} isn't set", fact)?;
+                } else {
+                    self.report(out)?;
+                }
+            },
+            ["clear"] => {
+                self.query.clear();
+                self.report(out)?;
+            },
+            ["facts"] => {
+                if self.query.facts().is_empty() {
+                    writeln!(out, "no facts are set")?;
+                }
+
+                for (fact, value) in self.query.facts() {
+                    writeln!(out, "{} = {}", fact, value)?;
+                }
+            },
+            ["help"] => writeln!(out, "{}", HELP)?,
+            ["quit"] | ["exit"] => return Ok(false),
+            _ => writeln!(out, "unknown command (type `help` for a list of commands)")?,
+        }
+
+        Ok(true)
+    }
+
+    /// Writes the rules that match the current query, and the rules that fail
+    /// by exactly one criterion.
+    fn report(&self, out: &mut impl Write) -> io::Result<()> {
+        let ruleset = &self.loaded.ruleset;
+        let matched = ruleset.evaluate_all(&self.query);
+
+        match matched.first() {
+            Some(first) => writeln!(
+                out,
+                "matched ({} {}):",
+                first.evaluators.len(),
+                crate::plural(first.evaluators.len(), "criterion", "criteria")
+            )?,
+            None => writeln!(out, "no rules matched")?,
+        }
+
+        for (index, rule) in ruleset.rules().iter().enumerate() {
+            if matched.iter().any(|x| core::ptr::eq(*x, rule)) {
+                writeln!(
+                    out,
+                    "  {} => {}",
+                    self.loaded.rule_id(index),
+                    load::outcome(&rule.outcome)
+                )?;
+            }
+        }
+
+        let near_misses = ruleset.near_misses(&self.query, 1);
+
+        if !near_misses.is_empty() {
+            writeln!(out, "near misses:")?;
+        }
+
+        for near_miss in near_misses {
+            writeln!(
+                out,
+                "  {} => {}: {}",
+                self.loaded.rule_id(near_miss.index),
+                load::outcome(&near_miss.rule.outcome),
+                load::failure(&near_miss.failures[0])
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn run(args: ReplArgs) -> Result<ExitCode> {
+    let mut session = Session {
+        loaded: load::ruleset(&args.ruleset)?,
+        query: Query::new(),
+    };
+
+    println!(
+        "loaded {} {} from {} (type `help` for a list of commands)",
+        session.loaded.ruleset.rules().len(),
+        crate::plural(session.loaded.ruleset.rules().len(), "rule", "rules"),
+        args.ruleset.display()
+    );
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut line = String::new();
+
+    loop {
+        print!("> ");
+        stdout.flush()?;
+
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 || !session.handle(&line, &mut stdout)? {
+            break;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn session() {
+        let mut hurt = Rule::named("hurt", json!("Ouch"));
+        hurt.insert("concept".to_string(), FloatEvaluator::EqualTo(1.));
+        hurt.insert("health".to_string(), FloatEvaluator::lt(0.5));
+
+        let mut greet = Rule::new(json!("Hello"));
+        greet.insert("concept".to_string(), FloatEvaluator::EqualTo(1.));
+
+        let mut session = Session {
+            loaded: Ruleset::new(vec![greet, hurt]).into(),
+            query: Query::new(),
+        };
+
+        let mut run = |line| {
+            let mut out = Vec::new();
+            assert!(session.handle(line, &mut out).unwrap());
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            run("set concept 1"),
+            "matched (1 criterion):\n  #0 => Hello\nnear misses:\n  hurt => Ouch: health is missing\n"
+        );
+        assert_eq!(
+            run("set health 0.25"),
+            "matched (2 criteria):\n  hurt => Ouch\n"
+        );
+        assert_eq!(run("set health low"), "expected a number, found low\n");
+        assert_eq!(run("unset mana"), "mana isn't set\n");
+        assert_eq!(run("facts"), "concept = 1\nhealth = 0.25\n");
+        assert!(!session.handle("quit", &mut Vec::new()).unwrap());
+    }
+}
@@ -0,0 +1,97 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::Value;
+use subtale_mimir::prelude::*;
+
+use crate::load;
+
+/// Runs scenario files against a ruleset, reporting the scenarios that fail.
+#[derive(Args)]
+pub struct TestArgs {
+    /// The ruleset file (JSON, or RON with a `.ron` extension).
+    ruleset: PathBuf,
+    /// The scenario files to run: lists of scenarios (JSON or RON).
+    #[arg(required = true)]
+    scenarios: Vec<PathBuf>,
+}
+
+/// Describes why a scenario failed.
+fn describe(failure: &ScenarioFailure<String, f64, Value>) -> String {
+    failure
+        .display_with(|outcome, f| write!(f, "{}", load::outcome(outcome)))
+        .to_string()
+}
+
+pub fn run(args: TestArgs) -> Result<ExitCode> {
+    let ruleset = load::ruleset(&args.ruleset)?.ruleset;
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for path in &args.scenarios {
+        let scenarios: Vec<Scenario<String, f64, Value>> = load::parse(path)?;
+        let failures = run_scenarios(&ruleset, &scenarios);
+        let mut failures = failures.iter().peekable();
+
+        for (index, scenario) in scenarios.iter().enumerate() {
+            // Failures are returned in the order of the scenarios
+            match failures.next_if(|failure| failure.index == index) {
+                Some(failure) => {
+                    failed += 1;
+                    println!(
+                        "FAILED {}: {}: {}",
+                        path.display(),
+                        scenario.name,
+                        describe(failure)
+                    );
+                },
+                None => {
+                    passed += 1;
+                    println!("ok     {}: {}", path.display(), scenario.name);
+                },
+            }
+        }
+    }
+
+    println!("{} passed, {} failed", passed, failed);
+
+    if failed > 0 {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn describe_failures() {
+        let mut hurt = Rule::new(json!("Ouch"));
+        hurt.insert("health".to_string(), FloatEvaluator::lt(0.5));
+        let mut hurt_alt = Rule::new(json!("Argh"));
+        hurt_alt.insert("health".to_string(), FloatEvaluator::lt(0.5));
+
+        let ruleset = Ruleset::new(vec![hurt, hurt_alt]);
+        let scenarios: Vec<Scenario<String, f64, Value>> = serde_json::from_value(json!([
+            {"name": "tied", "facts": {"health": 0.25}, "outcome": "Ouch"},
+            {"name": "none", "facts": {"health": 0.75}, "matches": ["Ouch"]},
+        ]))
+        .unwrap();
+
+        let failures = run_scenarios(&ruleset, &scenarios);
+        let messages: Vec<_> = failures.iter().map(describe).collect();
+
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("expected Ouch, but the most specific matches were "));
+        assert_eq!(
+            messages[1],
+            "expected the most specific matches to be Ouch, found nothing"
+        );
+    }
+}
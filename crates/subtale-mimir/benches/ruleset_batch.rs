@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use subtale_mimir::prelude::*;
+
+#[cfg(all(feature = "float", feature = "rayon"))]
+fn benchmark(c: &mut Criterion) {
+    let rules = (0..1_000)
+        .map(|outcome| {
+            let mut rule = Rule::new(outcome);
+            rule.insert("npc_id", FloatEvaluator::gte((outcome % 200) as f64));
+            rule.insert("health", FloatEvaluator::lt((outcome % 100) as f64));
+            rule
+        })
+        .collect();
+
+    let ruleset = Ruleset::new(rules);
+
+    let queries: Vec<_> = (0..200)
+        .map(|npc_id| {
+            let mut query = Query::new();
+            query.insert("npc_id", npc_id as f64);
+            query.insert("health", 50.);
+            query
+        })
+        .collect();
+    let queries: Vec<_> = queries.iter().collect();
+
+    let mut group = c.benchmark_group("ruleset evaluate 200 queries");
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .map(|query| ruleset.evaluate(query))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.bench_function("batch", |b| b.iter(|| ruleset.evaluate_batch(&queries, 0)));
+
+    group.finish();
+}
+
+#[cfg(all(feature = "float", feature = "rayon"))]
+criterion_group!(benches, benchmark);
+#[cfg(all(feature = "float", feature = "rayon"))]
+criterion_main!(benches);
@@ -1,6 +1,16 @@
 use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use subtale_mimir::prelude::*;
 
+#[cfg(feature = "float")]
+fn specific_rule() -> Rule<&'static str, f64, FloatEvaluator, ()> {
+    let mut rule = Rule::new(());
+    rule.insert("fact_1", FloatEvaluator::gt(0.5));
+    rule.insert("fact_2", FloatEvaluator::lt(6.0));
+    rule.insert("fact_3", FloatEvaluator::range(9.0, 12.0));
+    rule
+}
+
 #[cfg(feature = "float")]
 fn benchmark(c: &mut Criterion) {
     let mut query = Query::new();
@@ -12,17 +22,79 @@ fn benchmark(c: &mut Criterion) {
     rule_1.insert("fact_1", FloatEvaluator::lte(2.0));
     rule_1.insert("fact_2", FloatEvaluator::gte(3.0));
 
-    let mut rule_2 = Rule::new(());
-    rule_2.insert("fact_1", FloatEvaluator::gt(0.5));
-    rule_2.insert("fact_2", FloatEvaluator::lt(6.0));
-    rule_2.insert("fact_3", FloatEvaluator::range(9.0, 12.0));
+    let rule_2 = specific_rule();
+
+    c.bench_function("rule evaluate", |b| b.iter(|| rule_2.evaluate(&query)));
+
+    let mut registry = FactKeyRegistry::new();
+    let dense_rule = registry.dense_rule(specific_rule());
+    let dense_query = registry.dense_query(&query);
+
+    c.bench_function("dense rule evaluate", |b| {
+        b.iter(|| dense_rule.evaluate(&dense_query))
+    });
 
     let ruleset = Ruleset::new(vec![rule_1, rule_2]);
 
     c.bench_function("ruleset evaluate", |b| b.iter(|| ruleset.evaluate(&query)));
+
+    let compiled = CompiledRuleset::new(ruleset);
+    let dense_query = compiled.registry().dense_query(&query);
+
+    c.bench_function("compiled ruleset evaluate", |b| {
+        b.iter(|| compiled.evaluate(&dense_query))
+    });
+}
+
+#[cfg(feature = "float")]
+fn random_rules(num_rules: usize) -> Vec<Rule<usize, f64, FloatEvaluator, usize>> {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    (0..num_rules)
+        .map(|outcome| {
+            let mut rule = Rule::new(outcome);
+            for _ in 0..rng.gen_range(1..=8) {
+                rule.insert(
+                    rng.gen_range(0..32),
+                    FloatEvaluator::gte(rng.gen_range(0..=100) as f64),
+                );
+            }
+            rule
+        })
+        .collect()
+}
+
+#[cfg(feature = "float")]
+fn benchmark_large(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large ruleset evaluate");
+
+    let mut query = Query::new();
+    for fact in 0..32 {
+        query.insert(fact, 50.0);
+    }
+
+    for &num_rules in &[1_000, 10_000] {
+        let ruleset = Ruleset::new(random_rules(num_rules));
+        group.bench_function(format!("{} rules", num_rules), |b| {
+            b.iter(|| ruleset.evaluate_all(&query))
+        });
+
+        let compiled = CompiledRuleset::new(Ruleset::new(random_rules(num_rules)));
+        let dense_query = compiled.registry().dense_query(&query);
+        group.bench_function(format!("{} rules (compiled)", num_rules), |b| {
+            b.iter(|| compiled.evaluate_all(&dense_query))
+        });
+
+        let network = RuleNetwork::new(random_rules(num_rules));
+        group.bench_function(format!("{} rules (network)", num_rules), |b| {
+            b.iter(|| network.evaluate_all(&query))
+        });
+    }
+
+    group.finish();
 }
 
 #[cfg(feature = "float")]
-criterion_group!(benches, benchmark);
+criterion_group!(benches, benchmark, benchmark_large);
 #[cfg(feature = "float")]
 criterion_main!(benches);
@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::Rng;
+use subtale_mimir::prelude::*;
+
+fn benchmark(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+
+    let mut group = c.benchmark_group("ruleset load");
+
+    for &num_rules in &[1_000, 10_000] {
+        let rules: Vec<Rule<String, f64, FloatEvaluator, String>> = (0..num_rules)
+            .map(|outcome| {
+                let mut rule = Rule::new(format!("outcome {}", outcome));
+                for _ in 0..rng.gen_range(0..=10) {
+                    rule.insert(
+                        format!("fact {}", rng.gen_range(0..100)),
+                        FloatEvaluator::gte(rng.gen_range(0..=100) as f64),
+                    );
+                }
+                rule
+            })
+            .collect();
+
+        let ruleset = Ruleset::new(rules);
+        let json = serde_json::to_string(&ruleset).unwrap();
+        let bytes = ruleset.to_bytes().unwrap();
+        let compiled_bytes = CompiledRuleset::new(ruleset).to_bytes().unwrap();
+
+        group.bench_function(format!("{} rules (json)", num_rules), |b| {
+            b.iter(|| {
+                let _ruleset: Ruleset<&str, f64, FloatEvaluator, &str> =
+                    serde_json::from_str(&json).unwrap();
+            });
+        });
+
+        group.bench_function(format!("{} rules (binary)", num_rules), |b| {
+            b.iter(|| {
+                let _ruleset =
+                    Ruleset::<&str, f64, FloatEvaluator, &str>::from_bytes(&bytes).unwrap();
+            });
+        });
+
+        group.bench_function(format!("{} rules (binary, compiled)", num_rules), |b| {
+            b.iter(|| {
+                let _ruleset =
+                    CompiledRuleset::<&str, f64, FloatEvaluator, &str>::from_bytes(&compiled_bytes)
+                        .unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
@@ -0,0 +1,220 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The bytes at the start of every file written in Mímir's binary format.
+const MAGIC: &[u8; 4] = b"MIMR";
+
+/// The version of the binary format written by this version of Mímir.
+pub const BINARY_VERSION: u8 = 1;
+
+/// The kind of ruleset stored in a file written in Mímir's binary format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Kind {
+    Ruleset = 0,
+    Compiled = 1,
+}
+
+/// An error that occurred while encoding or decoding a ruleset in Mímir's
+/// binary format.
+#[derive(Debug)]
+pub enum BinaryError {
+    /// The bytes don't start with the header of Mímir's binary format.
+    InvalidHeader,
+    /// The bytes were written with a different version of the binary format.
+    UnsupportedVersion(u8),
+    /// The bytes contain a different kind of ruleset (e.g. a `Ruleset` was
+    /// loaded from the bytes of a `CompiledRuleset`).
+    UnexpectedKind,
+    /// The bytes were decoded, but don't describe a valid ruleset (e.g. the
+    /// rules of a `Ruleset` aren't sorted by specificity).
+    Corrupt,
+    /// The ruleset couldn't be encoded or decoded.
+    Encoding(postcard::Error),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid binary ruleset header"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported binary ruleset version {} (expected {})",
+                version, BINARY_VERSION
+            ),
+            Self::UnexpectedKind => write!(f, "unexpected kind of binary ruleset"),
+            Self::Corrupt => write!(f, "corrupt binary ruleset"),
+            Self::Encoding(error) => write!(f, "invalid binary ruleset: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BinaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Encoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Encodes the provided value (prefixed by the binary format's header).
+pub(crate) fn to_bytes<T: Serialize + ?Sized>(
+    kind: Kind,
+    value: &T,
+) -> Result<Vec<u8>, BinaryError> {
+    let mut bytes = Vec::from(&MAGIC[..]);
+    bytes.extend([BINARY_VERSION, kind as u8]);
+    postcard::to_extend(value, bytes).map_err(BinaryError::Encoding)
+}
+
+/// Decodes a value from the provided bytes (prefixed by the binary format's
+/// header).
+pub(crate) fn from_bytes<'a, T: Deserialize<'a>>(
+    kind: Kind,
+    bytes: &'a [u8],
+) -> Result<T, BinaryError> {
+    let header = MAGIC.len() + 2;
+
+    if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(BinaryError::InvalidHeader);
+    }
+
+    if bytes[MAGIC.len()] != BINARY_VERSION {
+        return Err(BinaryError::UnsupportedVersion(bytes[MAGIC.len()]));
+    }
+
+    if bytes[MAGIC.len() + 1] != kind as u8 {
+        return Err(BinaryError::UnexpectedKind);
+    }
+
+    match postcard::take_from_bytes(&bytes[header..]).map_err(BinaryError::Encoding)? {
+        (value, []) => Ok(value),
+        _ => Err(BinaryError::Corrupt),
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn ruleset() -> Ruleset<&'static str, f64, FloatEvaluator, &'static str> {
+        let mut rule = Rule::new("You killed 5 enemies!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+
+        let mut more_specific_rule =
+            Rule::named("doors", "You killed 5 enemies and opened 2 doors!");
+        more_specific_rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened", FloatEvaluator::range(2., 5.));
+
+        let mut other_rule = Rule::new("You opened a door!");
+        other_rule.insert("doors_opened", FloatEvaluator::gt(0.));
+
+        Ruleset::new(vec![rule, more_specific_rule, other_rule])
+    }
+
+    #[test]
+    fn ruleset_round_trip() {
+        let ruleset = ruleset();
+        let bytes = ruleset.to_bytes().unwrap();
+
+        let loaded: Ruleset<&str, f64, FloatEvaluator, &str> = Ruleset::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.rules().len(), 3);
+
+        for (loaded, rule) in loaded.rules().iter().zip(ruleset.rules()) {
+            assert_eq!(loaded.name, rule.name);
+            assert_eq!(loaded.evaluators, rule.evaluators);
+            assert_eq!(loaded.outcome, rule.outcome);
+        }
+    }
+
+    #[test]
+    fn compiled_ruleset_round_trip() {
+        let compiled = CompiledRuleset::new(ruleset());
+        let bytes = compiled.to_bytes().unwrap();
+
+        let loaded: CompiledRuleset<&str, f64, FloatEvaluator, &str> =
+            CompiledRuleset::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.len(), compiled.len());
+        assert_eq!(loaded.unique_criteria(), compiled.unique_criteria());
+
+        let mut query = Query::new();
+        query.insert("enemies_killed", 5.);
+        query.insert("doors_opened", 3.);
+        let query = loaded.registry().dense_query(&query);
+
+        assert_eq!(
+            loaded.evaluate_all(&query),
+            [&"You killed 5 enemies and opened 2 doors!"]
+        );
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let bytes = ruleset().to_bytes().unwrap();
+        type Loaded<'a> = Ruleset<&'a str, f64, FloatEvaluator, &'a str>;
+
+        assert!(matches!(
+            Loaded::from_bytes(b"MIM"),
+            Err(BinaryError::InvalidHeader)
+        ));
+
+        let mut version = bytes.clone();
+        version[4] = BINARY_VERSION + 1;
+        assert!(matches!(
+            Loaded::from_bytes(&version),
+            Err(BinaryError::UnsupportedVersion(_))
+        ));
+
+        assert!(matches!(
+            CompiledRuleset::<&str, f64, FloatEvaluator, &str>::from_bytes(&bytes),
+            Err(BinaryError::UnexpectedKind)
+        ));
+
+        assert!(matches!(
+            Loaded::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BinaryError::Encoding(_))
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            Loaded::from_bytes(&trailing),
+            Err(BinaryError::Corrupt)
+        ));
+
+        // Rules that aren't sorted by specificity are rejected
+        let mut unsorted = Rule::new("less specific");
+        unsorted.insert("a", FloatEvaluator::EqualTo(1.));
+        let mut sorted = Rule::new("more specific");
+        sorted.insert("a", FloatEvaluator::EqualTo(1.));
+        sorted.insert("b", FloatEvaluator::EqualTo(1.));
+
+        let rules: Vec<_> = [&unsorted, &sorted]
+            .iter()
+            .map(|rule| (&rule.name, &rule.evaluators, &rule.outcome))
+            .collect();
+        let bytes = to_bytes(Kind::Ruleset, &(rules, Vec::<u32>::new(), vec![0_usize, 1])).unwrap();
+        assert!(matches!(
+            Loaded::from_bytes(&bytes),
+            Err(BinaryError::Corrupt)
+        ));
+
+        // Invalid tolerances are rejected
+        let mut invalid = Rule::new("invalid");
+        invalid.insert(
+            "a",
+            FloatEvaluator::EqualWithin(1., FloatTolerance::Absolute(-0.1)),
+        );
+
+        let bytes = Ruleset::new(vec![invalid]).to_bytes().unwrap();
+        assert!(matches!(
+            Loaded::from_bytes(&bytes),
+            Err(BinaryError::Encoding(_))
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+use alloc::{vec, vec::Vec};
+use core::hash::{Hash, Hasher};
+
+use indexmap::IndexSet;
+
+use crate::hash::FactHasher;
+
+/// The slot of a criterion that isn't shared with any other rule (so its result
+/// is never cached).
+pub(crate) const UNSHARED: u32 = u32::MAX;
+
+/// A reusable cache of the results of the criteria shared between rules, used
+/// while evaluating a ruleset against a single query.
+///
+/// Criteria that are shared between several rules (the same fact key and an
+/// equal evaluator) are evaluated at most once per query, with the result
+/// reused by every rule that shares it (in a `CompiledRuleset`, or a `Ruleset`
+/// created with `Ruleset::with_shared_criteria`). Their `evaluate_all` methods
+/// create a new cache for each query, so pass the same cache to their
+/// `evaluate_all_with_cache` counterparts to avoid allocating it again (e.g.
+/// when evaluating a query every frame).
+///
+/// A cache can be reused across queries and rulesets: its results are
+/// invalidated in constant time at the start of each evaluation.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::new("You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+///
+/// let ruleset = Ruleset::with_shared_criteria(vec![rule]);
+/// let mut cache = CriterionCache::new();
+///
+/// for enemies_killed in [4., 5.] {
+///     let mut query = Query::new();
+///     query.insert("enemies_killed", enemies_killed);
+///
+///     let matched = ruleset.evaluate_all_with_cache(&query, &mut cache);
+///     assert_eq!(matched.len(), usize::from(enemies_killed == 5.));
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CriterionCache {
+    /// The generation in which each slot's result was cached, and the result.
+    slots: Vec<(u32, bool)>,
+    /// The current generation (bumped at the start of each evaluation, so that
+    /// results cached during previous evaluations are ignored).
+    generation: u32,
+}
+
+impl CriterionCache {
+    /// Creates a new, empty cache.
+    pub fn new() -> Self { Self::default() }
+
+    /// Invalidates all cached results.
+    pub(crate) fn reset(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+
+        // Once the generation wraps around, results cached many evaluations
+        // ago would appear to be current again
+        if self.generation == 0 {
+            self.slots.clear();
+            self.generation = 1;
+        }
+    }
+
+    /// Returns the cached result for the provided slot, or evaluates (and
+    /// caches) the result if it hasn't been cached since the last reset.
+    pub(crate) fn get_or_insert_with(
+        &mut self,
+        slot: u32,
+        evaluate: impl FnOnce() -> bool,
+    ) -> bool {
+        if slot == UNSHARED {
+            return evaluate();
+        }
+
+        let index = slot as usize;
+
+        if index >= self.slots.len() {
+            self.slots.resize(index + 1, (0, false));
+        }
+
+        let (generation, result) = self.slots[index];
+
+        if generation == self.generation {
+            return result;
+        }
+
+        let result = evaluate();
+        self.slots[index] = (self.generation, result);
+        result
+    }
+}
+
+/// A criterion (a fact key and its evaluator), compared by value.
+struct Criterion<'a, FactKey, FactEvaluator>(&'a FactKey, &'a FactEvaluator);
+
+impl<FactKey: Hash, FactEvaluator: Hash> Hash for Criterion<'_, FactKey, FactEvaluator> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+        self.1.hash(state);
+    }
+}
+
+impl<FactKey: Eq, FactEvaluator: PartialEq> PartialEq for Criterion<'_, FactKey, FactEvaluator> {
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 && self.1 == other.1 }
+}
+
+// Evaluators that aren't equal to themselves (e.g. comparisons against NaN)
+// are never found in the set, so they're simply never shared
+impl<FactKey: Eq, FactEvaluator: PartialEq> Eq for Criterion<'_, FactKey, FactEvaluator> {}
+
+/// Returns the id of each of the provided criteria, where identical criteria
+/// share an id (and ids are assigned in order of first occurrence).
+///
+/// Computes in `O(n)` expected time (where `n` is the number of criteria).
+pub(crate) fn dedup<'a, FactKey, FactEvaluator>(
+    criteria: impl IntoIterator<Item = (&'a FactKey, &'a FactEvaluator)>,
+) -> Vec<usize>
+where
+    FactKey: Hash + Eq + 'a,
+    FactEvaluator: Hash + PartialEq + 'a,
+{
+    let mut unique = IndexSet::<_, FactHasher>::default();
+
+    criteria
+        .into_iter()
+        .map(|(fact, evaluator)| unique.insert_full(Criterion(fact, evaluator)).0)
+        .collect()
+}
+
+/// Returns the cache slot of each unique criterion (given the id of every
+/// criterion, as returned by `dedup`).
+///
+/// Only criteria that are used more than once are given a slot, so that
+/// caching never costs more than evaluating a criterion that isn't shared.
+pub(crate) fn slots(ids: &[usize], unique: usize) -> Vec<u32> {
+    let mut counts = vec![0_usize; unique];
+
+    for id in ids {
+        counts[*id] += 1;
+    }
+
+    let mut next = 0;
+
+    counts
+        .into_iter()
+        .map(|count| {
+            if count > 1 {
+                next += 1;
+                next - 1
+            } else {
+                UNSHARED
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn criterion_dedup() {
+        let criteria = [
+            ("speaker", 1),
+            ("concept", 1),
+            ("speaker", 1),
+            ("speaker", 2),
+        ];
+        let ids = dedup(criteria.iter().map(|(fact, evaluator)| (fact, evaluator)));
+
+        assert_eq!(ids, [0, 1, 0, 2]);
+        assert_eq!(slots(&ids, 3), [0, UNSHARED, UNSHARED]);
+    }
+
+    #[test]
+    fn criterion_cache() {
+        let mut cache = CriterionCache::new();
+        cache.reset();
+
+        assert!(cache.get_or_insert_with(0, || true));
+        assert!(cache.get_or_insert_with(0, || unreachable!()));
+        assert!(!cache.get_or_insert_with(UNSHARED, || false));
+
+        cache.reset();
+        assert!(!cache.get_or_insert_with(0, || false));
+
+        // Results from before the generation wrapped around aren't reused
+        cache.generation = u32::MAX;
+        cache.get_or_insert_with(1, || true);
+        cache.reset();
+        assert!(!cache.get_or_insert_with(1, || false));
+    }
+}
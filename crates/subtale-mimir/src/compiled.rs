@@ -0,0 +1,468 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use rand::{seq::SliceRandom, Rng};
+#[cfg(feature = "binary")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "binary")]
+use crate::binary::{self, BinaryError, Kind};
+use crate::{
+    cache::{self, CriterionCache},
+    evaluator::Evaluator,
+    registry::{DenseQuery, FactId, FactKeyRegistry},
+    ruleset::Ruleset,
+};
+
+/// A `CompiledRuleset` is a read-only representation of a `Ruleset` that stores
+/// the evaluators of every rule in contiguous arrays, rather than a separate
+/// map per rule.
+///
+/// Evaluation has identical semantics to `Ruleset` (the most specific rules
+/// that evaluate to true are returned), but walks a handful of flat arrays
+/// instead of chasing a pointer to each rule's map.
+///
+/// Like `Ruleset`, each criterion that's shared between rules is evaluated at
+/// most once per query (see `CriterionCache`).
+///
+/// Fact keys are mapped to dense ids by an internal `FactKeyRegistry`, so
+/// compiled rulesets are evaluated against `DenseQuery` instances:
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::new("You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+///
+/// let ruleset = CompiledRuleset::new(Ruleset::new(vec![rule]));
+///
+/// let mut query = Query::new();
+/// query.insert("enemies_killed", 5.);
+/// let query = ruleset.registry().dense_query(&query);
+///
+/// assert_eq!(ruleset.evaluate(&query), Some(&"You killed 5 enemies!"));
+/// # }
+/// ```
+pub struct CompiledRuleset<FactKey, FactType, FactEvaluator: Evaluator<FactType>, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    marker: PhantomData<fn() -> FactType>,
+    registry: FactKeyRegistry<FactKey>,
+    /// The id of the fact checked by each unique criterion.
+    facts: Vec<FactId>,
+    /// The evaluator of each unique criterion.
+    evaluators: Vec<FactEvaluator>,
+    /// The index (into `facts` and `evaluators`) of each rule's criteria.
+    criteria: Vec<usize>,
+    /// The criteria of the rule at index `i` are stored in the range
+    /// `offsets[i]..offsets[i + 1]` of `criteria`.
+    offsets: Vec<usize>,
+    /// The outcome of each rule (in descending order of specificity).
+    outcomes: Vec<Outcome>,
+    /// The cache slot of each unique criterion.
+    slots: Vec<u32>,
+}
+
+impl<
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+        Outcome,
+    > CompiledRuleset<FactKey, FactType, FactEvaluator, Outcome>
+{
+    /// Compiles the provided ruleset.
+    ///
+    /// Computes in `O(n)` expected time (where `n` is the total number of
+    /// evaluators across all rules).
+    pub fn new(ruleset: Ruleset<FactKey, FactType, FactEvaluator, Outcome>) -> Self
+    where
+        FactEvaluator: core::hash::Hash + PartialEq,
+    {
+        let rules = ruleset.into_rules();
+        let criteria = cache::dedup(rules.iter().flat_map(|rule| &rule.evaluators));
+
+        let mut compiled = Self {
+            marker: PhantomData,
+            registry: FactKeyRegistry::new(),
+            facts: Vec::new(),
+            evaluators: Vec::new(),
+            criteria: Vec::new(),
+            offsets: Vec::with_capacity(rules.len() + 1),
+            outcomes: Vec::with_capacity(rules.len()),
+            slots: Vec::new(),
+        };
+
+        compiled.offsets.push(0);
+
+        let mut ids = criteria.iter();
+        let mut offset = 0;
+
+        for rule in rules {
+            offset += rule.evaluators.len();
+
+            // Criteria are given ids in order of first occurrence, so an id
+            // that hasn't been seen yet is always the next unique criterion
+            for ((fact, evaluator), id) in rule.evaluators.into_iter().zip(&mut ids) {
+                if *id == compiled.evaluators.len() {
+                    compiled.facts.push(compiled.registry.register(fact));
+                    compiled.evaluators.push(evaluator);
+                }
+            }
+
+            compiled.offsets.push(offset);
+            compiled.outcomes.push(rule.outcome);
+        }
+
+        compiled.slots = cache::slots(&criteria, compiled.evaluators.len());
+        compiled.criteria = criteria;
+        compiled
+    }
+
+    /// Returns the registry used to map the ruleset's fact keys to dense ids
+    /// (used to build the `DenseQuery` instances evaluated by the ruleset).
+    pub fn registry(&self) -> &FactKeyRegistry<FactKey> { &self.registry }
+
+    /// Returns the number of rules in the ruleset.
+    pub fn len(&self) -> usize { self.outcomes.len() }
+
+    /// Returns `true` if the ruleset doesn't contain any rules.
+    pub fn is_empty(&self) -> bool { self.outcomes.is_empty() }
+
+    /// Returns the number of unique criteria (fact key and evaluator pairs)
+    /// across all rules in the ruleset.
+    pub fn unique_criteria(&self) -> usize { self.evaluators.len() }
+
+    fn specificity(&self, rule: usize) -> usize { self.offsets[rule + 1] - self.offsets[rule] }
+
+    fn evaluate_rule(
+        &self,
+        rule: usize,
+        query: &DenseQuery<FactType>,
+        cache: &mut CriterionCache,
+    ) -> bool {
+        self.criteria[self.offsets[rule]..self.offsets[rule + 1]]
+            .iter()
+            .all(|criterion| {
+                cache.get_or_insert_with(self.slots[*criterion], || {
+                    query
+                        .get(self.facts[*criterion])
+                        .is_some_and(|value| self.evaluators[*criterion].evaluate(value))
+                })
+            })
+    }
+
+    /// Evaluates the ruleset against the provided query.
+    ///
+    /// Returns the outcomes of the most specific (most evaluators) rules in
+    /// the ruleset that evaluate to true for the provided query (in the same
+    /// order as `Ruleset::evaluate_all`).
+    pub fn evaluate_all(&self, query: &DenseQuery<FactType>) -> Vec<&Outcome> {
+        self.evaluate_all_with_cache(query, &mut CriterionCache::new())
+    }
+
+    /// Evaluates the ruleset against the provided query, caching the results
+    /// of shared criteria in the provided cache (see
+    /// `Ruleset::evaluate_all_with_cache`).
+    pub fn evaluate_all_with_cache(
+        &self,
+        query: &DenseQuery<FactType>,
+        cache: &mut CriterionCache,
+    ) -> Vec<&Outcome> {
+        let mut matched = Vec::<&Outcome>::new();
+        let mut tier = 0;
+
+        cache.reset();
+
+        for rule in 0..self.len() {
+            let specificity = self.specificity(rule);
+
+            if specificity < tier {
+                break;
+            }
+
+            if self.evaluate_rule(rule, query, cache) {
+                tier = specificity;
+                matched.push(&self.outcomes[rule]);
+            }
+        }
+
+        matched
+    }
+
+    /// Evaluates the ruleset against the provided query.
+    ///
+    /// Returns the outcome of the most specific (most evaluators) rule in the
+    /// ruleset that evaluates to true for the provided query. If multiple rules
+    /// evaluate to true with the same specificness, one is picked at random.
+    #[cfg(feature = "std")]
+    pub fn evaluate(&self, query: &DenseQuery<FactType>) -> Option<&Outcome> {
+        self.evaluate_with_rng(query, &mut rand::thread_rng())
+    }
+
+    /// Evaluates the ruleset against the provided query, breaking ties between
+    /// equally specific rules with the provided random number generator (see
+    /// `Ruleset::evaluate_with_rng`).
+    ///
+    /// Unlike `CompiledRuleset::evaluate`, this is available without the `std`
+    /// feature.
+    pub fn evaluate_with_rng<R: Rng + ?Sized>(
+        &self,
+        query: &DenseQuery<FactType>,
+        rng: &mut R,
+    ) -> Option<&Outcome> {
+        let matched = self.evaluate_all(query);
+        matched.choose(rng).copied()
+    }
+}
+
+#[cfg(feature = "binary")]
+impl<
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+        Outcome,
+    > CompiledRuleset<FactKey, FactType, FactEvaluator, Outcome>
+{
+    /// Encodes the compiled ruleset in Mímir's compact binary format.
+    ///
+    /// Loading the ruleset with `CompiledRuleset::from_bytes` skips compiling
+    /// it (and sorting its rules) again, so rulesets can be compiled offline
+    /// (e.g. when building your game's assets).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BinaryError>
+    where
+        FactKey: Serialize,
+        FactEvaluator: Serialize,
+        Outcome: Serialize,
+    {
+        binary::to_bytes(
+            Kind::Compiled,
+            &(
+                &self.registry,
+                &self.facts,
+                &self.evaluators,
+                &self.criteria,
+                &self.offsets,
+                &self.outcomes,
+            ),
+        )
+    }
+
+    /// Decodes a compiled ruleset from bytes written by
+    /// `CompiledRuleset::to_bytes`.
+    ///
+    /// Fact keys and outcomes that borrow from the input (e.g. `&str`) aren't
+    /// copied, so the bytes can be loaded directly from a memory-mapped file.
+    pub fn from_bytes<'a>(bytes: &'a [u8]) -> Result<Self, BinaryError>
+    where
+        FactKey: Deserialize<'a>,
+        FactEvaluator: Deserialize<'a>,
+        Outcome: Deserialize<'a>,
+    {
+        let (registry, facts, evaluators, criteria, offsets, outcomes) =
+            binary::from_bytes(Kind::Compiled, bytes)?;
+
+        let mut compiled = Self {
+            marker: PhantomData,
+            registry,
+            facts,
+            evaluators,
+            criteria,
+            offsets,
+            outcomes,
+            slots: Vec::new(),
+        };
+
+        if !compiled.is_valid() {
+            return Err(BinaryError::Corrupt);
+        }
+
+        compiled.slots = cache::slots(&compiled.criteria, compiled.evaluators.len());
+        Ok(compiled)
+    }
+
+    /// Returns `true` if the ruleset's arrays are consistent with each other
+    /// (so that evaluating the ruleset can't index out of bounds).
+    fn is_valid(&self) -> bool {
+        self.facts.len() == self.evaluators.len()
+            && self
+                .facts
+                .iter()
+                .all(|fact| fact.index() < self.registry.len())
+            && self
+                .criteria
+                .iter()
+                .all(|criterion| *criterion < self.evaluators.len())
+            && self.offsets.len() == self.outcomes.len() + 1
+            && self.offsets.first() == Some(&0)
+            && self.offsets.last() == Some(&self.criteria.len())
+            && self
+                .offsets
+                .windows(2)
+                .all(|offsets| offsets[0] <= offsets[1])
+            && (1..self.len()).all(|rule| self.specificity(rule - 1) >= self.specificity(rule))
+    }
+}
+
+impl<
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy + core::hash::Hash + PartialEq,
+        Outcome,
+    > From<Ruleset<FactKey, FactType, FactEvaluator, Outcome>>
+    for CompiledRuleset<FactKey, FactType, FactEvaluator, Outcome>
+{
+    fn from(ruleset: Ruleset<FactKey, FactType, FactEvaluator, Outcome>) -> Self {
+        Self::new(ruleset)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use crate::prelude::*;
+
+    fn rules() -> Vec<Rule<&'static str, f64, FloatEvaluator, &'static str>> {
+        let mut rule = Rule::new("You killed 5 enemies!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+
+        let mut other_rule = Rule::new("You killed some enemies!");
+        other_rule.insert("enemies_killed", FloatEvaluator::gt(0.));
+
+        let mut more_specific_rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+        more_specific_rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened", FloatEvaluator::gt(2.));
+
+        let fallback_rule = Rule::new("Nothing happened.");
+
+        vec![rule, other_rule, more_specific_rule, fallback_rule]
+    }
+
+    #[test]
+    fn compiled_ruleset_evaluation() {
+        let ruleset = CompiledRuleset::new(Ruleset::new(rules()));
+        assert_eq!(ruleset.len(), 4);
+
+        let mut query = Query::new();
+        query.insert("enemies_killed", 2.5 + 1.5 + 1.);
+        let dense_query = ruleset.registry().dense_query(&query);
+
+        let mut matched = ruleset.evaluate_all(&dense_query);
+        matched.sort();
+        assert_eq!(
+            matched,
+            vec![&"You killed 5 enemies!", &"You killed some enemies!"]
+        );
+
+        query.insert("doors_opened", 10.);
+        let dense_query = ruleset.registry().dense_query(&query);
+
+        assert_eq!(
+            ruleset.evaluate(&dense_query),
+            Some(&"You killed 5 enemies and opened 2 doors!")
+        );
+
+        assert_eq!(
+            ruleset.evaluate(&DenseQuery::new()),
+            Some(&"Nothing happened.")
+        );
+    }
+
+    #[test]
+    fn compiled_ruleset_deduplicates_criteria() {
+        let ruleset = CompiledRuleset::new(Ruleset::new(rules()));
+
+        // `enemies_killed == 5` is shared by two rules
+        assert_eq!(ruleset.unique_criteria(), 3);
+    }
+
+    #[test]
+    fn shared_criteria_evaluated_once() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static EVALUATIONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[derive(Clone, Copy, Hash, PartialEq)]
+        struct CountingEvaluator(u32);
+
+        impl Evaluator<f64> for CountingEvaluator {
+            fn evaluate(self, value: f64) -> bool {
+                EVALUATIONS.with(|evaluations| evaluations.set(evaluations.get() + 1));
+                value == f64::from(self.0)
+            }
+        }
+
+        let rules = || {
+            (0..10)
+                .map(|outcome| {
+                    let mut rule = Rule::new(outcome);
+                    rule.insert("speaker", CountingEvaluator(1));
+                    rule.insert("concept", CountingEvaluator(outcome));
+                    rule
+                })
+                .collect()
+        };
+
+        let compiled = CompiledRuleset::new(Ruleset::new(rules()));
+        assert_eq!(compiled.unique_criteria(), 11);
+
+        let mut query = Query::new();
+        query.insert("speaker", 1.);
+        query.insert("concept", 3.);
+
+        assert_eq!(
+            compiled.evaluate_all(&compiled.registry().dense_query(&query)),
+            vec![&3]
+        );
+        assert_eq!(EVALUATIONS.with(Cell::get), 11);
+
+        // Uncompiled rulesets only share criteria when asked to
+        EVALUATIONS.with(|evaluations| evaluations.set(0));
+        assert_eq!(Ruleset::new(rules()).evaluate_all(&query).len(), 1);
+        assert_eq!(EVALUATIONS.with(Cell::get), 20);
+
+        // A cache can be reused between queries
+        let ruleset = Ruleset::with_shared_criteria(rules());
+        let mut cache = CriterionCache::new();
+
+        for _ in 0..2 {
+            EVALUATIONS.with(|evaluations| evaluations.set(0));
+
+            let matched = ruleset.evaluate_all_with_cache(&query, &mut cache);
+            assert_eq!(matched.len(), 1);
+            assert_eq!(matched[0].outcome, 3);
+            assert_eq!(EVALUATIONS.with(Cell::get), 11);
+        }
+    }
+
+    #[test]
+    fn compiled_ruleset_matches_ruleset() {
+        let ruleset = Ruleset::new(rules());
+        let compiled = CompiledRuleset::from(Ruleset::new(rules()));
+
+        for enemies_killed in [0., 1., 5.] {
+            for doors_opened in [None, Some(1.), Some(3.)] {
+                let mut query = Query::new();
+                query.insert("enemies_killed", enemies_killed);
+
+                if let Some(doors_opened) = doors_opened {
+                    query.insert("doors_opened", doors_opened);
+                }
+
+                let expected: Vec<_> = ruleset
+                    .evaluate_all(&query)
+                    .into_iter()
+                    .map(|rule| &rule.outcome)
+                    .collect();
+
+                assert_eq!(
+                    compiled.evaluate_all(&compiled.registry().dense_query(&query)),
+                    expected
+                );
+            }
+        }
+    }
+}
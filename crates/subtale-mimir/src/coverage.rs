@@ -0,0 +1,319 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use rand::{seq::SliceRandom, Rng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{evaluator::Evaluator, query::Query, rule::Rule, ruleset::Ruleset};
+
+/// The number of times that a rule matched (and was selected by) the queries
+/// recorded in a `Coverage` report.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RuleCoverage {
+    /// The rule's name (if it has one).
+    pub name: Option<String>,
+    /// The number of queries that the rule matched (whether or not it was the
+    /// most specific match).
+    pub matched: u64,
+    /// The number of queries for which the rule was selected.
+    pub selected: u64,
+    /// The number of queries for which the rule was one of the most specific
+    /// matches, but another equally specific rule was selected instead.
+    pub lost_ties: u64,
+}
+
+/// A `Coverage` report counts how often each rule in a ruleset matched a
+/// query, was selected, or lost a tie to an equally specific rule, as recorded
+/// by `Ruleset::evaluate_with_coverage`.
+///
+/// This is useful for finding rules that are never selected during automated
+/// playthroughs of your game. Recording coverage evaluates every rule in the
+/// ruleset (rather than stopping at the most specific matches), so it's opt-in.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use rand::{rngs::StdRng, SeedableRng};
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::new("You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+///
+/// let mut more_specific_rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+/// more_specific_rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+/// more_specific_rule.insert("doors_opened", FloatEvaluator::gt(2.));
+///
+/// let ruleset = Ruleset::new(vec![rule, more_specific_rule]);
+/// let mut coverage = Coverage::new(&ruleset);
+/// let mut rng = StdRng::seed_from_u64(0);
+///
+/// let mut query = Query::new();
+/// query.insert("enemies_killed", 5.);
+/// ruleset.evaluate_with_coverage(&query, &mut rng, &mut coverage);
+///
+/// assert_eq!(coverage.never_selected().collect::<Vec<_>>(), [0]);
+/// # }
+/// ```
+///
+/// Reports can be exported as CSV with `Coverage::to_csv`, or (when the
+/// `serde` feature is enabled) serialized as a map with the number of
+/// `evaluations` and a list of `rules` (in the order of `Ruleset::rules`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Coverage {
+    evaluations: u64,
+    rules: Vec<RuleCoverage>,
+}
+
+impl Coverage {
+    /// Instantiates an empty coverage report for the provided ruleset.
+    pub fn new<FactKey, FactType, FactEvaluator, Outcome>(
+        ruleset: &Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    ) -> Self
+    where
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+    {
+        Self {
+            evaluations: 0,
+            rules: ruleset
+                .rules()
+                .iter()
+                .map(|rule| RuleCoverage {
+                    name: rule.name.clone(),
+                    ..RuleCoverage::default()
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of queries recorded in the report.
+    pub fn evaluations(&self) -> u64 { self.evaluations }
+
+    /// Returns the coverage of each rule (in the order of `Ruleset::rules`).
+    pub fn rules(&self) -> &[RuleCoverage] { &self.rules }
+
+    /// Returns the index of each rule that was never selected.
+    pub fn never_selected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.selected == 0)
+            .map(|(index, _)| index)
+    }
+
+    /// Adds the counts from another report (e.g. from another playthrough) to
+    /// this report.
+    ///
+    /// Panics if the reports were created for rulesets with a different number
+    /// of rules.
+    pub fn merge(&mut self, other: &Coverage) {
+        assert_eq!(
+            self.rules.len(),
+            other.rules.len(),
+            "coverage reports are for different rulesets"
+        );
+
+        self.evaluations += other.evaluations;
+
+        for (rule, other) in self.rules.iter_mut().zip(&other.rules) {
+            rule.matched += other.matched;
+            rule.selected += other.selected;
+            rule.lost_ties += other.lost_ties;
+        }
+    }
+
+    /// Exports the report as CSV, with a row for each rule (in the order of
+    /// `Ruleset::rules`) and the columns `rule` (the rule's index), `name`,
+    /// `matched`, `selected` and `lost_ties`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("rule,name,matched,selected,lost_ties\n");
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            // Writing to a `String` never fails
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                index,
+                CsvField(rule.name.as_deref().unwrap_or_default()),
+                rule.matched,
+                rule.selected,
+                rule.lost_ties
+            );
+        }
+
+        csv
+    }
+}
+
+/// A CSV field, quoted if it contains a comma, quote or line break.
+struct CsvField<'a>(&'a str);
+
+impl fmt::Display for CsvField<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.contains([',', '"', '\n', '\r']) {
+            write!(f, "\"{}\"", self.0.replace('"', "\"\""))
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl<
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+        Outcome,
+    > Ruleset<FactKey, FactType, FactEvaluator, Outcome>
+{
+    /// Evaluates the ruleset against the provided query (in the same way as
+    /// `Ruleset::evaluate_with_rng`), recording which rules matched and which
+    /// rule was selected in the provided coverage report.
+    ///
+    /// Given the same generator, this selects the same rule as
+    /// `Ruleset::evaluate_with_rng`. Unlike `Ruleset::evaluate_with_rng`, every
+    /// rule in the ruleset is evaluated.
+    ///
+    /// Panics if the report wasn't created for a ruleset with the same number
+    /// of rules.
+    pub fn evaluate_with_coverage<R: Rng + ?Sized>(
+        &self,
+        query: &Query<FactKey, FactType>,
+        rng: &mut R,
+        coverage: &mut Coverage,
+    ) -> Option<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        let rules = self.rules();
+
+        assert_eq!(
+            coverage.rules.len(),
+            rules.len(),
+            "coverage report is for a different ruleset"
+        );
+
+        coverage.evaluations += 1;
+
+        // The indices of the most specific rules that match the query
+        let mut matched = Vec::<usize>::new();
+
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.evaluate(query) {
+                coverage.rules[index].matched += 1;
+
+                // Rules are sorted by specificity, so a less specific rule
+                // can't be one of the most specific matches
+                match matched.first() {
+                    Some(&first) if rules[first].evaluators.len() > rule.evaluators.len() => {},
+                    _ => matched.push(index),
+                }
+            }
+        }
+
+        let selected = matched.choose(rng).copied();
+
+        for index in matched {
+            if Some(index) == selected {
+                coverage.rules[index].selected += 1;
+            } else {
+                coverage.rules[index].lost_ties += 1;
+            }
+        }
+
+        selected.map(|index| &rules[index])
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        prelude::*,
+        testing::{dialogue, index},
+    };
+
+    #[test]
+    fn coverage() {
+        let ruleset = dialogue();
+        let mut coverage = Coverage::new(&ruleset);
+
+        let mut query = Query::new();
+        query.insert("concept", 1.);
+        query.insert("health", 0.1);
+
+        for seed in 0..10 {
+            let selected = ruleset.evaluate_with_coverage(
+                &query,
+                &mut StdRng::seed_from_u64(seed),
+                &mut coverage,
+            );
+
+            assert!(core::ptr::eq(
+                selected.unwrap(),
+                ruleset
+                    .evaluate_with_rng(&query, &mut StdRng::seed_from_u64(seed))
+                    .unwrap()
+            ));
+        }
+
+        assert_eq!(coverage.evaluations(), 10);
+
+        // The two equally specific rules always tie
+        let tied = &coverage.rules()[..2];
+        assert!(tied.iter().all(|rule| rule.matched == 10));
+        assert_eq!(tied[0].selected + tied[1].selected, 10);
+        assert_eq!(tied[0].lost_ties, tied[1].selected);
+
+        // The less specific rules are never selected
+        let greet = index(&ruleset, "Hello!");
+        assert_eq!(coverage.rules()[greet].matched, 10);
+        assert_eq!(coverage.rules()[greet].lost_ties, 0);
+        assert!(coverage.never_selected().any(|rule| rule == greet));
+        assert!(coverage
+            .never_selected()
+            .any(|rule| rule == index(&ruleset, "Phew!")));
+
+        let mut merged = coverage.clone();
+        merged.merge(&coverage);
+        assert_eq!(merged.evaluations(), 20);
+        assert_eq!(merged.rules()[greet].matched, 20);
+    }
+
+    #[test]
+    fn coverage_csv() {
+        let ruleset = dialogue();
+        let mut coverage = Coverage::new(&ruleset);
+
+        let mut query = Query::new();
+        query.insert("stamina", 0.25);
+        ruleset.evaluate_with_coverage(&query, &mut StdRng::seed_from_u64(0), &mut coverage);
+
+        let csv = coverage.to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+
+        let tired = index(&ruleset, "Phew!");
+        let hurt = index(&ruleset, "Ouch!");
+
+        assert_eq!(lines[0], "rule,name,matched,selected,lost_ties");
+        assert_eq!(lines[tired + 1], format!("{},tired,1,1,0", tired));
+        assert_eq!(lines[hurt + 1], format!("{},\"hurt, badly\",0,0,0", hurt));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn coverage_serialization() {
+        let ruleset = dialogue();
+        let coverage = Coverage::new(&ruleset);
+
+        let json = serde_json::to_value(&coverage).unwrap();
+        assert_eq!(json["evaluations"], 0);
+        let tired = index(&ruleset, "Phew!");
+        assert_eq!(json["rules"][tired]["name"], "tired");
+        assert_eq!(json["rules"][tired]["lost_ties"], 0);
+
+        let loaded: Coverage = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded, coverage);
+    }
+}
@@ -0,0 +1,532 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, mem};
+
+use crate::{
+    float::{FloatEvaluator, FloatRangeBound},
+    rule::Rule,
+    ruleset::Ruleset,
+};
+
+/// The type of ruleset produced by importing rules from CSV.
+pub type CsvRuleset = Ruleset<String, f64, FloatEvaluator, String>;
+
+/// The type of rule produced by importing rules from CSV.
+pub type CsvRule = Rule<String, f64, FloatEvaluator, String>;
+
+/// The names of the columns in a CSV file that don't contain criteria.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvColumns<'a> {
+    /// The column containing each rule's name (optional, both in the file and
+    /// in each row).
+    pub name: Option<&'a str>,
+    /// The column containing each rule's outcome.
+    pub outcome: &'a str,
+}
+
+/// By default, rule names are read from a `name` column and outcomes from an
+/// `outcome` column.
+impl Default for CsvColumns<'_> {
+    fn default() -> Self {
+        Self {
+            name: Some("name"),
+            outcome: "outcome",
+        }
+    }
+}
+
+/// An error encountered while importing rules from CSV.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvError {
+    /// The row (starting at 1 for the header row) at which the error occurred.
+    pub row: usize,
+    /// The column at which the error occurred (if the error is specific to a
+    /// cell).
+    pub column: Option<String>,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl CsvError {
+    fn new(row: usize, column: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            row,
+            column: column.map(Into::into),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "row {}, column {:?}: {}", self.row, column, self.message),
+            None => write!(f, "row {}: {}", self.row, self.message),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CsvError {}
+
+/// Imports rules from CSV (e.g. exported from a spreadsheet) into a ruleset.
+///
+/// The first row is a header row containing the fact key checked by each
+/// column, along with the columns that contain each rule's name and outcome
+/// (see `CsvColumns`). Every other row is a rule, whose cells contain the
+/// criteria for each fact:
+///
+/// | Cell     | Criterion                               |
+/// |----------|-----------------------------------------|
+/// | (empty)  | The fact isn't checked                  |
+/// | `5`      | `FloatEvaluator::EqualTo(5.)`           |
+/// | `!=5`    | `FloatEvaluator::NotEqualTo(5.)`        |
+/// | `<5`     | `FloatEvaluator::lt(5.)`                |
+/// | `<=5`    | `FloatEvaluator::lte(5.)`               |
+/// | `>5`     | `FloatEvaluator::gt(5.)`                |
+/// | `>=5`    | `FloatEvaluator::gte(5.)`               |
+/// | `3..10`  | `FloatEvaluator::range(3., 10.)`        |
+/// | `3..=10` | A range that includes its upper bound   |
+///
+/// Ranges that can never be satisfied (e.g. `10..3`) are rejected. Only
+/// `FloatEvaluator`s are produced, so every fact is a number.
+///
+/// Cells can be quoted (with double quotes) to contain commas, double quotes
+/// (written twice) or line breaks, and whitespace before the opening quote is
+/// ignored. Whitespace around unquoted cells (including outcomes) is trimmed,
+/// whereas quoted outcomes are kept as they are. Blank rows are ignored, and
+/// every other row must have the same number of cells as the header row. A
+/// leading byte order mark (e.g. added by spreadsheet software when exporting
+/// as UTF-8) is ignored.
+///
+/// ```
+/// use subtale_mimir::{
+///     csv::{self, CsvColumns},
+///     prelude::*,
+/// };
+///
+/// let ruleset = csv::parse(
+///     "name,enemies_killed,health,outcome\n\
+///      kills,>=5,,You killed 5 enemies!\n\
+///      hurt,>=5,<25,\"Ouch, that hurt!\"\n",
+///     &CsvColumns::default(),
+/// )
+/// .unwrap();
+///
+/// let mut query = Query::new();
+/// query.insert("enemies_killed".to_string(), 6.);
+/// query.insert("health".to_string(), 10.);
+///
+/// assert_eq!(
+///     ruleset.evaluate(&query).unwrap().outcome,
+///     "Ouch, that hurt!"
+/// );
+/// ```
+pub fn parse(source: &str, columns: &CsvColumns<'_>) -> Result<CsvRuleset, CsvError> {
+    let rules = parse_rules(source, columns)?;
+    Ok(Ruleset::new(
+        rules.into_iter().map(|(_, rule)| rule).collect(),
+    ))
+}
+
+/// Imports rules from CSV (see `csv::parse`), returning the rules in the order
+/// of their rows, along with the row (starting at 1 for the header row) that
+/// each rule was read from (e.g. to report problems with a rule).
+pub fn parse_rules(
+    source: &str,
+    columns: &CsvColumns<'_>,
+) -> Result<Vec<(usize, CsvRule)>, CsvError> {
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let mut records = records(source)?.into_iter();
+
+    let Some((header_row, header)) = records.next() else {
+        return Err(CsvError::new(1, None, "missing header row"));
+    };
+
+    let header: Vec<&str> = header.iter().map(|key| key.text.trim()).collect();
+
+    for (index, key) in header.iter().enumerate() {
+        if key.is_empty() {
+            return Err(CsvError::new(
+                header_row,
+                None,
+                format!("column {} has no name", index + 1),
+            ));
+        }
+
+        if header[..index].contains(key) {
+            return Err(CsvError::new(
+                header_row,
+                Some(key),
+                "the column appears more than once",
+            ));
+        }
+    }
+
+    let position = |column: &str| header.iter().position(|key| *key == column);
+
+    let outcome = position(columns.outcome).ok_or_else(|| {
+        CsvError::new(
+            header_row,
+            None,
+            format!("missing {:?} column", columns.outcome),
+        )
+    })?;
+    let name = columns.name.and_then(position);
+
+    let mut rules = Vec::new();
+
+    for (row, record) in records {
+        if record.len() != header.len() {
+            return Err(CsvError::new(
+                row,
+                None,
+                format!(
+                    "expected {} cells (one for each column), found {}",
+                    header.len(),
+                    record.len()
+                ),
+            ));
+        }
+
+        if record[outcome].text.trim().is_empty() {
+            return Err(CsvError::new(row, Some(header[outcome]), "missing outcome"));
+        }
+
+        let mut rule = Rule::new(match &record[outcome] {
+            Cell { text, quoted: true } => text.clone(),
+            Cell {
+                text,
+                quoted: false,
+            } => text.trim().to_string(),
+        });
+
+        for (index, cell) in record.iter().enumerate() {
+            let cell = cell.text.trim();
+
+            if Some(index) == name {
+                rule.name = (!cell.is_empty()).then(|| cell.to_string());
+            } else if index != outcome && !cell.is_empty() {
+                let evaluator = criterion(cell)
+                    .map_err(|message| CsvError::new(row, Some(header[index]), message))?;
+
+                rule.insert(header[index].to_string(), evaluator);
+            }
+        }
+
+        rules.push((row, rule));
+    }
+
+    Ok(rules)
+}
+
+/// Parses a cell containing a criterion (e.g. `>=5` or `3..10`), returning a
+/// description of the problem if the cell is invalid.
+fn criterion(cell: &str) -> Result<FloatEvaluator, String> {
+    let invalid = || {
+        format!(
+            "expected a number, a comparison (e.g. `>=5`) or a range (e.g. `3..10`), found {:?}",
+            cell
+        )
+    };
+
+    if let Some((lower, upper)) = cell.split_once("..") {
+        // e.g. `3...5`, which would otherwise be parsed as `3..0.5`
+        if upper.starts_with('.') {
+            return Err(invalid());
+        }
+
+        let (upper, inclusive) = match upper.strip_prefix('=') {
+            Some(upper) => (upper, true),
+            None => (upper, false),
+        };
+
+        let lower = number(lower).ok_or_else(invalid)?;
+        let upper = number(upper).ok_or_else(invalid)?;
+
+        if lower > upper || (lower == upper && !inclusive) {
+            return Err(format!(
+                "the range {:?} is empty, so it can never be satisfied",
+                cell
+            ));
+        }
+
+        return Ok(FloatEvaluator::InRange(
+            FloatRangeBound::Inclusive(lower),
+            if inclusive {
+                FloatRangeBound::Inclusive(upper)
+            } else {
+                FloatRangeBound::Exclusive(upper)
+            },
+        ));
+    }
+
+    // Longer operators are checked first, so that `<=` isn't parsed as `<`
+    for operator in ["!=", "==", "<=", ">=", "<", ">", "="] {
+        if let Some(value) = cell.strip_prefix(operator) {
+            let value = number(value).ok_or_else(invalid)?;
+
+            return Ok(match operator {
+                "!=" => FloatEvaluator::NotEqualTo(value),
+                "<=" => FloatEvaluator::lte(value),
+                ">=" => FloatEvaluator::gte(value),
+                "<" => FloatEvaluator::lt(value),
+                ">" => FloatEvaluator::gt(value),
+                _ => FloatEvaluator::EqualTo(value),
+            });
+        }
+    }
+
+    number(cell)
+        .map(FloatEvaluator::EqualTo)
+        .ok_or_else(invalid)
+}
+
+/// Parses a finite number (surrounded by optional whitespace).
+fn number(text: &str) -> Option<f64> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// A cell in a CSV record.
+struct Cell {
+    text: String,
+    /// Whether the cell was quoted.
+    quoted: bool,
+}
+
+/// Splits CSV into records of cells, along with the row number of each
+/// record (ignoring blank rows).
+fn records(source: &str) -> Result<Vec<(usize, Vec<Cell>)>, CsvError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    // Whether the current cell is quoted
+    let mut quoted_cell = false;
+    // Whether the current row contains a quoted cell (so isn't blank)
+    let mut quoted = false;
+    let mut row = 1;
+
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // Whitespace before a quoted cell (e.g. after `, `) is ignored
+            '"' if cell.trim_start().is_empty() => {
+                cell.clear();
+                quoted = true;
+                quoted_cell = true;
+
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            cell.push('"');
+                        },
+                        Some('"') => break,
+                        Some(c) => cell.push(c),
+                        None => return Err(CsvError::new(row, None, "unterminated quoted cell")),
+                    }
+                }
+            },
+            ',' => record.push(Cell {
+                text: mem::take(&mut cell),
+                quoted: mem::take(&mut quoted_cell),
+            }),
+            '\r' if chars.peek() == Some(&'\n') => {},
+            '\n' => {
+                record.push(Cell {
+                    text: mem::take(&mut cell),
+                    quoted: mem::take(&mut quoted_cell),
+                });
+
+                if quoted || record.len() > 1 || !record[0].text.is_empty() {
+                    records.push((row, mem::take(&mut record)));
+                } else {
+                    record.clear();
+                }
+
+                quoted = false;
+                row += 1;
+            },
+            c => cell.push(c),
+        }
+    }
+
+    if quoted || !record.is_empty() || !cell.is_empty() {
+        record.push(Cell {
+            text: cell,
+            quoted: quoted_cell,
+        });
+        records.push((row, record));
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        parse_rules(source, &CsvColumns::default())
+            .err()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn parse_criteria() {
+        let cases = [
+            ("5", FloatEvaluator::EqualTo(5.)),
+            ("=5", FloatEvaluator::EqualTo(5.)),
+            ("== -5", FloatEvaluator::EqualTo(-5.)),
+            ("!=2", FloatEvaluator::NotEqualTo(2.)),
+            ("<5", FloatEvaluator::lt(5.)),
+            ("<= 5", FloatEvaluator::lte(5.)),
+            (">0.5", FloatEvaluator::gt(0.5)),
+            (">=5", FloatEvaluator::gte(5.)),
+            ("3..10", FloatEvaluator::range(3., 10.)),
+            ("-1.5..2.5", FloatEvaluator::range(-1.5, 2.5)),
+            (
+                "3..=10",
+                FloatEvaluator::InRange(
+                    FloatRangeBound::Inclusive(3.),
+                    FloatRangeBound::Inclusive(10.),
+                ),
+            ),
+        ];
+
+        for (cell, expected) in cases {
+            assert_eq!(criterion(cell), Ok(expected), "{}", cell);
+        }
+
+        assert_eq!(
+            criterion("5..=5"),
+            Ok(FloatEvaluator::InRange(
+                FloatRangeBound::Inclusive(5.),
+                FloatRangeBound::Inclusive(5.),
+            ))
+        );
+
+        for cell in [
+            "abc", ">=", "3..", "..10", "=>5", "nan", "5..inf", "3...5", "3..5..7",
+        ] {
+            assert!(criterion(cell).is_err(), "{}", cell);
+        }
+
+        for cell in ["10..3", "5..5", "5..=4"] {
+            assert_eq!(
+                criterion(cell),
+                Err(format!(
+                    "the range {:?} is empty, so it can never be satisfied",
+                    cell
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn parse_rules_from_csv() {
+        let rules = parse_rules(
+            "name,enemies_killed,health,outcome\r\n\
+             kills,>=5,, You killed 5 enemies! \r\n\
+             \r\n\
+             ,>=5, <25 , \"Ouch, \"\"that\"\"\nhurt!\"\r\n",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+
+        // Blank rows are skipped, but still counted
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].0, 2);
+        assert_eq!(rules[1].0, 4);
+
+        let rules: Vec<_> = rules.into_iter().map(|(_, rule)| rule).collect();
+        assert_eq!(rules[0].name.as_deref(), Some("kills"));
+        assert_eq!(rules[0].evaluators.len(), 1);
+        assert_eq!(
+            rules[0].evaluators["enemies_killed"],
+            FloatEvaluator::gte(5.)
+        );
+        assert_eq!(rules[0].outcome, "You killed 5 enemies!");
+
+        assert_eq!(rules[1].name, None);
+        assert_eq!(rules[1].evaluators["health"], FloatEvaluator::lt(25.));
+        assert_eq!(rules[1].outcome, "Ouch, \"that\"\nhurt!");
+
+        // Quoted outcomes keep their whitespace
+        let rules = parse_rules("health,outcome\n<25,\" Ouch! \"", &CsvColumns::default()).unwrap();
+        assert_eq!(rules[0].1.outcome, " Ouch! ");
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let rules = parse_rules(
+            "\u{feff}name,health,outcome\nhurt,<25,Ouch!\n",
+            &CsvColumns::default(),
+        )
+        .unwrap();
+
+        assert_eq!(rules[0].1.name.as_deref(), Some("hurt"));
+        assert_eq!(rules[0].1.evaluators.len(), 1);
+        assert_eq!(rules[0].1.evaluators["health"], FloatEvaluator::lt(25.));
+    }
+
+    #[test]
+    fn custom_columns() {
+        let columns = CsvColumns {
+            name: None,
+            outcome: "line",
+        };
+
+        let rules = parse_rules("health,line,name\n<25,Ouch!,5", &columns).unwrap();
+
+        assert_eq!(rules[0].1.name, None);
+        assert_eq!(rules[0].1.outcome, "Ouch!");
+        assert_eq!(rules[0].1.evaluators["name"], FloatEvaluator::EqualTo(5.));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(error(""), "row 1: missing header row");
+        assert_eq!(error("health,line\n"), "row 1: missing \"outcome\" column");
+        assert_eq!(error("health,,outcome"), "row 1: column 2 has no name");
+        assert_eq!(
+            error("health,health,outcome"),
+            "row 1, column \"health\": the column appears more than once"
+        );
+        assert_eq!(
+            error("health,outcome\n<25,Ouch!\n\n<25,Ouch!,extra"),
+            "row 4: expected 2 cells (one for each column), found 3"
+        );
+        assert_eq!(
+            error("health,outcome\n<25,Ouch!\n>=25, "),
+            "row 3, column \"outcome\": missing outcome"
+        );
+        assert_eq!(
+            error("health,outcome\n<25,Ouch!\n=>25,Phew!"),
+            "row 3, column \"health\": expected a number, a comparison (e.g. `>=5`) or a range \
+             (e.g. `3..10`), found \"=>25\""
+        );
+        assert_eq!(
+            error("health,outcome\n<25,\"Ouch!\n"),
+            "row 2: unterminated quoted cell"
+        );
+        assert_eq!(
+            error("health,outcome\n3...5,Ouch!"),
+            "row 2, column \"health\": expected a number, a comparison (e.g. `>=5`) or a range \
+             (e.g. `3..10`), found \"3...5\""
+        );
+        assert_eq!(
+            error("health,outcome\n<25,Ouch!\n50..25,Phew!"),
+            "row 3, column \"health\": the range \"50..25\" is empty, so it can never be \
+             satisfied"
+        );
+    }
+}
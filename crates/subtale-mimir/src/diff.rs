@@ -0,0 +1,446 @@
+use alloc::vec::Vec;
+
+use indexmap::IndexMap;
+
+use crate::{evaluator::Evaluator, hash::FactHasher, rule::Rule, ruleset::Ruleset};
+
+/// The semantic difference between two versions of a ruleset, as returned by
+/// `diff_rulesets`.
+///
+/// Rules are referred to by their index in the ruleset that contains them (in
+/// the order of `Ruleset::rules`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RulesetDiff<'a, FactKey, FactEvaluator, Outcome> {
+    /// The rules in the new ruleset that aren't in the old ruleset.
+    pub added: Vec<usize>,
+    /// The rules in the old ruleset that aren't in the new ruleset.
+    pub removed: Vec<usize>,
+    /// The rules in both rulesets that have changed or moved to a different
+    /// tier (in the order of the new ruleset).
+    pub changed: Vec<RuleDiff<'a, FactKey, FactEvaluator, Outcome>>,
+}
+
+impl<FactKey, FactEvaluator, Outcome> RulesetDiff<'_, FactKey, FactEvaluator, Outcome> {
+    /// Returns `true` if the rulesets contain the same rules.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The changes made to a rule that's in both versions of a ruleset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleDiff<'a, FactKey, FactEvaluator, Outcome> {
+    /// The index of the rule in the old ruleset.
+    pub old: usize,
+    /// The index of the rule in the new ruleset.
+    pub new: usize,
+    /// The rule's criteria that were added, removed or changed (added and
+    /// changed criteria are in the order of the new rule, followed by removed
+    /// criteria).
+    pub criteria: Vec<CriterionChange<'a, FactKey, FactEvaluator>>,
+    /// The rule's old and new outcomes, if its outcome changed.
+    pub outcome: Option<(&'a Outcome, &'a Outcome)>,
+    /// The rule's old and new specificity (number of criteria), if it changed.
+    pub specificity: Option<(usize, usize)>,
+    /// The rule's old and new tier, if it changed.
+    ///
+    /// A rule's tier is the number of distinct specificities above its own
+    /// among the rules in both rulesets (so rules in tier 0 are evaluated
+    /// first, and rules in the same tier can tie). Rules that were added or
+    /// removed don't affect the tiers, so a rule moves tiers when its order
+    /// relative to the other rules changes: e.g. an unchanged rule moves when a
+    /// more specific rule loses enough criteria to tie with it.
+    pub tier: Option<(usize, usize)>,
+}
+
+/// A change to a single criterion (a fact and its evaluator) of a rule.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CriterionChange<'a, FactKey, FactEvaluator> {
+    /// The criterion was added to the rule.
+    Added {
+        /// The fact checked by the criterion.
+        fact: &'a FactKey,
+        /// The fact's evaluator.
+        evaluator: &'a FactEvaluator,
+    },
+    /// The criterion was removed from the rule.
+    Removed {
+        /// The fact checked by the criterion.
+        fact: &'a FactKey,
+        /// The fact's evaluator.
+        evaluator: &'a FactEvaluator,
+    },
+    /// The evaluator for the fact was changed.
+    Changed {
+        /// The fact checked by the criterion.
+        fact: &'a FactKey,
+        /// The fact's old evaluator.
+        old: &'a FactEvaluator,
+        /// The fact's new evaluator.
+        new: &'a FactEvaluator,
+    },
+}
+
+/// Compares two versions of a ruleset, returning the rules that were added,
+/// removed or changed.
+///
+/// Rules are identified by their name (see `Rule::name`), so a named rule is
+/// changed rather than removed and added when its criteria or outcome change.
+/// If several rules share a name, they're paired up in the order of the
+/// rulesets' rules. Unnamed rules can only be identified by their contents, so
+/// they're either unchanged, or removed and added.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::named("kills", "You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+/// let old = Ruleset::new(vec![rule]);
+///
+/// let mut rule = Rule::named("kills", "You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::gte(5.));
+/// let new = Ruleset::new(vec![rule]);
+///
+/// let diff = diff_rulesets(&old, &new);
+///
+/// assert_eq!(
+///     diff.changed[0].criteria,
+///     [CriterionChange::Changed {
+///         fact: &"enemies_killed",
+///         old: &FloatEvaluator::EqualTo(5.),
+///         new: &FloatEvaluator::gte(5.),
+///     }]
+/// );
+/// # }
+/// ```
+pub fn diff_rulesets<'a, FactKey, FactType, FactEvaluator, Outcome>(
+    old: &'a Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    new: &'a Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+) -> RulesetDiff<'a, FactKey, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy + PartialEq,
+    Outcome: PartialEq,
+{
+    let (old_rules, new_rules) = (old.rules(), new.rules());
+
+    // The indices of the old ruleset's rules, grouped by name (in order)
+    let mut named = IndexMap::<&str, Vec<usize>, FactHasher>::default();
+    let mut unnamed = Vec::new();
+
+    for (index, rule) in old_rules.iter().enumerate() {
+        match &rule.name {
+            Some(name) => named.entry(name.as_str()).or_default().push(index),
+            None => unnamed.push(index),
+        }
+    }
+
+    for group in named.values_mut() {
+        group.reverse();
+    }
+
+    let mut paired = Vec::with_capacity(new_rules.len());
+    let mut added = Vec::new();
+
+    for (index, rule) in new_rules.iter().enumerate() {
+        let old_index = match &rule.name {
+            Some(name) => named.get_mut(name.as_str()).and_then(Vec::pop),
+            None => unnamed
+                .iter()
+                .position(|&old| is_identical(&old_rules[old], rule))
+                .map(|position| unnamed.remove(position)),
+        };
+
+        match old_index {
+            Some(old_index) => paired.push((old_index, index)),
+            None => added.push(index),
+        }
+    }
+
+    let mut removed: Vec<_> = named.into_values().flatten().chain(unnamed).collect();
+    removed.sort_unstable();
+
+    // The distinct specificities of the paired rules in each ruleset
+    let old_tiers = tiers(
+        paired
+            .iter()
+            .map(|&(old, _)| old_rules[old].evaluators.len()),
+    );
+    let new_tiers = tiers(
+        paired
+            .iter()
+            .map(|&(_, new)| new_rules[new].evaluators.len()),
+    );
+
+    let changed = paired
+        .into_iter()
+        .filter_map(|(old_index, new_index)| {
+            let (old_rule, new_rule) = (&old_rules[old_index], &new_rules[new_index]);
+
+            let mut criteria: Vec<_> = new_rule
+                .evaluators
+                .iter()
+                .filter_map(|(fact, new)| match old_rule.evaluators.get(fact) {
+                    None => Some(CriterionChange::Added {
+                        fact,
+                        evaluator: new,
+                    }),
+                    Some(old) if old != new => Some(CriterionChange::Changed { fact, old, new }),
+                    Some(_) => None,
+                })
+                .collect();
+
+            criteria.extend(
+                old_rule
+                    .evaluators
+                    .iter()
+                    .filter(|(fact, _)| !new_rule.evaluators.contains_key(*fact))
+                    .map(|(fact, evaluator)| CriterionChange::Removed { fact, evaluator }),
+            );
+
+            let outcome = (old_rule.outcome != new_rule.outcome)
+                .then_some((&old_rule.outcome, &new_rule.outcome));
+
+            let specificity = (old_rule.evaluators.len() != new_rule.evaluators.len())
+                .then_some((old_rule.evaluators.len(), new_rule.evaluators.len()));
+
+            let (old_tier, new_tier) = (
+                tier(&old_tiers, old_rule.evaluators.len()),
+                tier(&new_tiers, new_rule.evaluators.len()),
+            );
+            let tier = (old_tier != new_tier).then_some((old_tier, new_tier));
+
+            let changed = !criteria.is_empty() || outcome.is_some() || tier.is_some();
+
+            changed.then_some(RuleDiff {
+                old: old_index,
+                new: new_index,
+                criteria,
+                outcome,
+                specificity,
+                tier,
+            })
+        })
+        .collect();
+
+    RulesetDiff {
+        added,
+        removed,
+        changed,
+    }
+}
+
+/// Returns the distinct specificities in ascending order.
+fn tiers(specificities: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut tiers: Vec<_> = specificities.collect();
+    tiers.sort_unstable();
+    tiers.dedup();
+    tiers
+}
+
+/// Returns the tier of a rule with the provided specificity (the number of
+/// distinct specificities above it).
+fn tier(tiers: &[usize], specificity: usize) -> usize {
+    tiers.len() - tiers.partition_point(|&tier| tier <= specificity)
+}
+
+/// Returns `true` if the rules have identical criteria and outcomes.
+fn is_identical<FactKey, FactType, FactEvaluator, Outcome>(
+    a: &Rule<FactKey, FactType, FactEvaluator, Outcome>,
+    b: &Rule<FactKey, FactType, FactEvaluator, Outcome>,
+) -> bool
+where
+    FactKey: core::hash::Hash + Eq,
+    FactEvaluator: Evaluator<FactType> + PartialEq,
+    Outcome: PartialEq,
+{
+    a.evaluators == b.evaluators && a.outcome == b.outcome
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use crate::{
+        prelude::*,
+        testing::{index, named, rule},
+    };
+
+    #[test]
+    fn ruleset_diff() {
+        let old = Ruleset::new(vec![
+            named(
+                "greet",
+                "Hello!",
+                &[("concept", FloatEvaluator::EqualTo(1.))],
+            ),
+            named(
+                "hurt",
+                "Ouch!",
+                &[
+                    ("concept", FloatEvaluator::EqualTo(1.)),
+                    ("health", FloatEvaluator::lt(0.5)),
+                ],
+            ),
+            named("tired", "Phew!", &[("stamina", FloatEvaluator::lt(0.5))]),
+            rule("Hmm.", &[("concept", FloatEvaluator::EqualTo(2.))]),
+            rule("Huh?", &[("concept", FloatEvaluator::EqualTo(3.))]),
+        ]);
+
+        let new = Ruleset::new(vec![
+            named(
+                "greet",
+                "Hello!",
+                &[("concept", FloatEvaluator::EqualTo(1.))],
+            ),
+            named(
+                "hurt",
+                "Ow!",
+                &[
+                    ("concept", FloatEvaluator::EqualTo(1.)),
+                    ("health", FloatEvaluator::lt(0.25)),
+                    ("enemies", FloatEvaluator::gt(0.)),
+                ],
+            ),
+            rule("Hmm.", &[("concept", FloatEvaluator::EqualTo(2.))]),
+            rule("Huh?!", &[("concept", FloatEvaluator::EqualTo(3.))]),
+            named("bored", "Yawn.", &[("stamina", FloatEvaluator::gt(0.9))]),
+        ]);
+
+        let diff = diff_rulesets(&old, &new);
+
+        let mut added = diff.added.clone();
+        added.sort_unstable();
+        let mut expected_added = vec![index(&new, "Huh?!"), index(&new, "Yawn.")];
+        expected_added.sort_unstable();
+        assert_eq!(added, expected_added);
+
+        let mut expected_removed = vec![index(&old, "Phew!"), index(&old, "Huh?")];
+        expected_removed.sort_unstable();
+        assert_eq!(diff.removed, expected_removed);
+
+        assert_eq!(
+            diff.changed,
+            [RuleDiff {
+                old: index(&old, "Ouch!"),
+                new: index(&new, "Ow!"),
+                criteria: vec![
+                    CriterionChange::Changed {
+                        fact: &"health",
+                        old: &FloatEvaluator::lt(0.5),
+                        new: &FloatEvaluator::lt(0.25),
+                    },
+                    CriterionChange::Added {
+                        fact: &"enemies",
+                        evaluator: &FloatEvaluator::gt(0.),
+                    },
+                ],
+                outcome: Some((&"Ouch!", &"Ow!")),
+                specificity: Some((2, 3)),
+                tier: None,
+            }]
+        );
+
+        assert!(!diff.is_empty());
+        assert!(diff_rulesets(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn removed_criteria() {
+        let old = Ruleset::new(vec![named(
+            "hurt",
+            "Ouch!",
+            &[
+                ("concept", FloatEvaluator::EqualTo(1.)),
+                ("health", FloatEvaluator::lt(0.5)),
+            ],
+        )]);
+        let new = Ruleset::new(vec![named(
+            "hurt",
+            "Ouch!",
+            &[("concept", FloatEvaluator::EqualTo(1.))],
+        )]);
+
+        let diff = diff_rulesets(&old, &new);
+
+        assert_eq!(
+            diff.changed[0].criteria,
+            [CriterionChange::Removed {
+                fact: &"health",
+                evaluator: &FloatEvaluator::lt(0.5),
+            }]
+        );
+        assert_eq!(diff.changed[0].outcome, None);
+        assert_eq!(diff.changed[0].specificity, Some((2, 1)));
+        assert_eq!(diff.changed[0].tier, None);
+    }
+
+    #[test]
+    fn tier_changes() {
+        let old = Ruleset::new(vec![
+            named(
+                "hurt",
+                "Ouch!",
+                &[
+                    ("concept", FloatEvaluator::EqualTo(1.)),
+                    ("health", FloatEvaluator::lt(0.5)),
+                ],
+            ),
+            named(
+                "greet",
+                "Hello!",
+                &[("concept", FloatEvaluator::EqualTo(1.))],
+            ),
+            named("tired", "Phew!", &[("stamina", FloatEvaluator::lt(0.5))]),
+        ]);
+
+        // "hurt" loses a criterion, so it's now in the same tier as the
+        // unchanged rules (which move up to the first tier)
+        let rules = || {
+            vec![
+                named("hurt", "Ouch!", &[("health", FloatEvaluator::lt(0.5))]),
+                named(
+                    "greet",
+                    "Hello!",
+                    &[("concept", FloatEvaluator::EqualTo(1.))],
+                ),
+                named("tired", "Phew!", &[("stamina", FloatEvaluator::lt(0.5))]),
+            ]
+        };
+        let new = Ruleset::new(rules());
+
+        let diff = diff_rulesets(&old, &new);
+        let tiers: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|change| (new.rules()[change.new].outcome, change.tier))
+            .collect();
+
+        assert_eq!(diff.changed.len(), 3);
+        assert!(tiers.contains(&("Ouch!", None)));
+        assert!(tiers.contains(&("Hello!", Some((1, 0)))));
+        assert!(tiers.contains(&("Phew!", Some((1, 0)))));
+
+        let greet = diff
+            .changed
+            .iter()
+            .find(|change| change.new == index(&new, "Hello!"))
+            .unwrap();
+        assert!(greet.criteria.is_empty());
+        assert_eq!(greet.specificity, None);
+
+        // Rules that are added or removed don't move the other rules
+        let mut added = rules();
+        added.push(rule(
+            "Hmm.",
+            &[
+                ("concept", FloatEvaluator::EqualTo(1.)),
+                ("health", FloatEvaluator::lt(0.5)),
+                ("stamina", FloatEvaluator::lt(0.5)),
+            ],
+        ));
+        let added = Ruleset::new(added);
+        assert_eq!(diff_rulesets(&new, &added).changed, []);
+    }
+}
@@ -0,0 +1,774 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{
+    evaluator::Evaluator,
+    float::{FloatEvaluator, FloatRangeBound},
+    lint::Analyze,
+    rule::Rule,
+    ruleset::Ruleset,
+    schema::{Typed, ValueType},
+};
+
+/// The value of a fact in a query evaluated against rules written in Mímir's
+/// rule authoring language (see `dsl::parse`).
+///
+/// Text values borrow their contents, so that the value remains `Copy`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value<'a> {
+    /// A numeric value (compared using `FloatEvaluator`).
+    Number(f64),
+    /// A boolean value.
+    Bool(bool),
+    /// A text value (e.g. the name of a concept or speaker).
+    Text(&'a str),
+}
+
+impl From<f64> for Value<'_> {
+    fn from(value: f64) -> Self { Self::Number(value) }
+}
+
+impl From<bool> for Value<'_> {
+    fn from(value: bool) -> Self { Self::Bool(value) }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(value: &'a str) -> Self { Self::Text(value) }
+}
+
+impl Typed for Value<'_> {
+    fn value_type(&self) -> ValueType {
+        match self {
+            Self::Number(_) => ValueType::Number,
+            Self::Bool(_) => ValueType::Bool,
+            Self::Text(_) => ValueType::Text,
+        }
+    }
+}
+
+/// An implementation of the `Evaluator` trait for `Value`, produced by the
+/// criteria of rules written in Mímir's rule authoring language.
+///
+/// A fact whose value is of a different type to the evaluator (e.g. a number
+/// compared against text) never satisfies the evaluator.
+#[derive(Clone, Copy, Debug, Hash, PartialEq)]
+pub enum ValueEvaluator<'a> {
+    /// Checks a numeric fact using a `FloatEvaluator`.
+    Number(FloatEvaluator),
+    /// Checks if a boolean fact has a specific value.
+    Bool(bool),
+    /// Checks if a text fact has a specific value.
+    Text(&'a str),
+    /// Checks if a text fact does not have a specific value.
+    NotText(&'a str),
+}
+
+impl<'a> Evaluator<Value<'a>> for ValueEvaluator<'a> {
+    fn evaluate(self, value: Value<'a>) -> bool {
+        match (self, value) {
+            (Self::Number(evaluator), Value::Number(x)) => evaluator.evaluate(x),
+            (Self::Bool(expected), Value::Bool(x)) => expected == x,
+            (Self::Text(expected), Value::Text(x)) => expected == x,
+            (Self::NotText(expected), Value::Text(x)) => expected != x,
+            _ => false,
+        }
+    }
+}
+
+impl Typed for ValueEvaluator<'_> {
+    fn value_type(&self) -> ValueType {
+        match self {
+            Self::Number(_) => ValueType::Number,
+            Self::Bool(_) => ValueType::Bool,
+            Self::Text(_) | Self::NotText(_) => ValueType::Text,
+        }
+    }
+}
+
+impl Analyze for ValueEvaluator<'_> {
+    fn is_satisfiable(&self) -> bool {
+        match self {
+            Self::Number(evaluator) => evaluator.is_satisfiable(),
+            _ => true,
+        }
+    }
+
+    fn implies(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => a.implies(b),
+            (Self::Number(a), _) => !a.is_satisfiable(),
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Text(a), Self::Text(b)) | (Self::NotText(a), Self::NotText(b)) => a == b,
+            (Self::Text(a), Self::NotText(b)) => a != b,
+            _ => false,
+        }
+    }
+
+    /// Facts are assumed to hold values of the types that they're compared
+    /// against, so the evaluators cover every value if they cover every value
+    /// of each type that they compare against (e.g. `speaker == "Player"` and
+    /// `speaker != "Player"` cover every speaker).
+    fn covers(evaluators: &[Self]) -> bool {
+        let numbers: Vec<_> = evaluators
+            .iter()
+            .filter_map(|evaluator| match evaluator {
+                Self::Number(evaluator) => Some(*evaluator),
+                _ => None,
+            })
+            .collect();
+
+        let has = |value_type| {
+            evaluators
+                .iter()
+                .any(|evaluator| evaluator.value_type() == value_type)
+        };
+
+        let bools = [true, false]
+            .iter()
+            .all(|value| evaluators.contains(&Self::Bool(*value)));
+
+        // Text is covered by two different `NotText` evaluators, or by a
+        // `NotText` evaluator alongside a `Text` evaluator for the same value
+        let text = evaluators.iter().any(|a| match a {
+            Self::NotText(x) => evaluators.iter().any(|b| match b {
+                Self::NotText(y) => x != y,
+                Self::Text(y) => x == y,
+                _ => false,
+            }),
+            _ => false,
+        });
+
+        !evaluators.is_empty()
+            && (!has(ValueType::Number) || FloatEvaluator::covers(&numbers))
+            && (!has(ValueType::Bool) || bools)
+            && (!has(ValueType::Text) || text)
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => a.overlaps(b),
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Text(a), Self::Text(b)) => a == b,
+            (Self::Text(a), Self::NotText(b)) | (Self::NotText(a), Self::Text(b)) => a != b,
+            (Self::NotText(_), Self::NotText(_)) => true,
+            // A fact can't hold values of two different types
+            _ => false,
+        }
+    }
+}
+
+/// The type of ruleset produced by parsing Mímir's rule authoring language.
+pub type DslRuleset<'a> = Ruleset<&'a str, Value<'a>, ValueEvaluator<'a>, &'a str>;
+
+/// The type of rule produced by parsing Mímir's rule authoring language.
+pub type DslRule<'a> = Rule<&'a str, Value<'a>, ValueEvaluator<'a>, &'a str>;
+
+/// An error encountered while parsing Mímir's rule authoring language.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// The line (starting at 1) at which the error occurred.
+    pub line: usize,
+    /// The column (starting at 1, counted in characters) at which the error
+    /// occurred.
+    pub column: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Parses a collection of rules written in Mímir's rule authoring language
+/// into a ruleset.
+///
+/// Each rule has a name, a block of criteria (separated by semicolons) and an
+/// outcome:
+///
+/// ```text
+/// # comments start with a hash (or two slashes)
+/// rule greet_low_health {
+///     concept == "Greet";
+///     health < 20;
+/// } => "You look hurt"
+/// ```
+///
+/// Criteria compare a fact against a number (`==`, `!=`, `<`, `<=`, `>`,
+/// `>=`), text (`==`, `!=`) or a boolean (`==`, `!=`). Two numeric criteria
+/// for the same fact can be combined into a range (e.g. `health >= 5; health <
+/// 25;`). Text can't contain double quotes.
+///
+/// ```
+/// use subtale_mimir::{
+///     dsl::{self, Value},
+///     prelude::*,
+/// };
+///
+/// let ruleset = dsl::parse(
+///     r#"
+///     rule greet { concept == "Greet"; } => "Hello!"
+///     rule greet_low_health { concept == "Greet"; health < 20; } => "You look hurt"
+/// "#,
+/// )
+/// .unwrap();
+///
+/// let mut query = Query::new();
+/// query.insert("concept", Value::from("Greet"));
+/// query.insert("health", Value::from(10.));
+///
+/// assert_eq!(ruleset.evaluate(&query).unwrap().outcome, "You look hurt");
+/// ```
+pub fn parse(source: &str) -> Result<DslRuleset<'_>, ParseError> {
+    parse_rules(source).map(Ruleset::new)
+}
+
+/// Parses a collection of rules written in Mímir's rule authoring language
+/// (see `dsl::parse`), returning the rules in the order they were written.
+pub fn parse_rules(source: &str) -> Result<Vec<DslRule<'_>>, ParseError> {
+    let mut parser = Parser {
+        lexer: Lexer::new(source),
+        peeked: None,
+    };
+
+    let mut rules = Vec::new();
+
+    while parser.peek()?.is_some() {
+        rules.push(parser.rule()?);
+    }
+
+    Ok(rules)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Number(f64),
+    Text(&'a str),
+    Operator(Operator),
+    OpenBrace,
+    CloseBrace,
+    Semicolon,
+    Arrow,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(ident) => write!(f, "`{}`", ident),
+            Self::Number(number) => write!(f, "number `{}`", number),
+            Self::Text(text) => write!(f, "text \"{}\"", text),
+            Self::Operator(operator) => write!(f, "`{}`", operator),
+            Self::OpenBrace => write!(f, "`{{`"),
+            Self::CloseBrace => write!(f, "`}}`"),
+            Self::Semicolon => write!(f, "`;`"),
+            Self::Arrow => write!(f, "`=>`"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+        })
+    }
+}
+
+/// A token (or the end of the source) along with its position.
+type Spanned<'a> = (Option<Token<'a>>, usize, usize);
+
+struct Lexer<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str { &self.source[self.offset..] }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.rest().chars().next()?;
+        self.offset += c.len_utf8();
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn bump_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+
+        while self.rest().chars().next().is_some_and(&predicate) {
+            self.bump();
+        }
+
+        &self.source[start..self.offset]
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            self.bump_while(char::is_whitespace);
+
+            if self.rest().starts_with('#') || self.rest().starts_with("//") {
+                self.bump_while(|c| c != '\n');
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<Spanned<'a>, ParseError> {
+        self.skip_whitespace_and_comments();
+
+        let (line, column) = (self.line, self.column);
+        let rest = self.rest();
+
+        let Some(c) = rest.chars().next() else {
+            return Ok((None, line, column));
+        };
+
+        let token = if c.is_alphabetic() || c == '_' {
+            Token::Ident(self.bump_while(|c| c.is_alphanumeric() || c == '_' || c == '.'))
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            self.bump();
+            let digits = self.bump_while(|c| c.is_ascii_digit() || c == '.');
+            let number = &rest[..c.len_utf8() + digits.len()];
+
+            Token::Number(number.parse().map_err(|_| ParseError {
+                line,
+                column,
+                message: format!("invalid number `{}`", number),
+            })?)
+        } else if c == '"' {
+            self.bump();
+            let text = self.bump_while(|c| c != '"' && c != '\n');
+
+            if self.bump() != Some('"') {
+                return Err(ParseError {
+                    line,
+                    column,
+                    message: "unterminated text (text must end with `\"` on the same line)"
+                        .to_string(),
+                });
+            }
+
+            Token::Text(text)
+        } else {
+            let (token, len) = match rest.as_bytes() {
+                [b'=', b'=', ..] => (Token::Operator(Operator::Equal), 2),
+                [b'!', b'=', ..] => (Token::Operator(Operator::NotEqual), 2),
+                [b'<', b'=', ..] => (Token::Operator(Operator::LessEqual), 2),
+                [b'>', b'=', ..] => (Token::Operator(Operator::GreaterEqual), 2),
+                [b'=', b'>', ..] => (Token::Arrow, 2),
+                [b'<', ..] => (Token::Operator(Operator::Less), 1),
+                [b'>', ..] => (Token::Operator(Operator::Greater), 1),
+                [b'{', ..] => (Token::OpenBrace, 1),
+                [b'}', ..] => (Token::CloseBrace, 1),
+                [b';', ..] => (Token::Semicolon, 1),
+                _ => return Err(self.error(format!("unexpected character `{}`", c))),
+            };
+
+            for _ in 0..len {
+                self.bump();
+            }
+
+            token
+        };
+
+        Ok((Some(token), line, column))
+    }
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Spanned<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Result<Option<Token<'a>>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lexer.next()?);
+        }
+
+        Ok(self.peeked.unwrap().0)
+    }
+
+    fn next(&mut self) -> Result<Spanned<'a>, ParseError> {
+        match self.peeked.take() {
+            Some(spanned) => Ok(spanned),
+            None => self.lexer.next(),
+        }
+    }
+
+    fn unexpected((token, line, column): Spanned<'a>, expected: &str) -> ParseError {
+        let found = match token {
+            Some(token) => token.to_string(),
+            None => "end of input".to_string(),
+        };
+
+        ParseError {
+            line,
+            column,
+            message: format!("expected {}, found {}", expected, found),
+        }
+    }
+
+    fn expect(&mut self, expected: Token<'a>) -> Result<(), ParseError> {
+        let spanned = self.next()?;
+
+        if spanned.0 == Some(expected) {
+            Ok(())
+        } else {
+            Err(Self::unexpected(spanned, &expected.to_string()))
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<(&'a str, usize, usize), ParseError> {
+        match self.next()? {
+            (Some(Token::Ident(ident)), line, column) => Ok((ident, line, column)),
+            spanned => Err(Self::unexpected(spanned, expected)),
+        }
+    }
+
+    fn rule(&mut self) -> Result<DslRule<'a>, ParseError> {
+        match self.ident("`rule`")? {
+            ("rule", ..) => {},
+            (ident, line, column) => {
+                return Err(Self::unexpected(
+                    (Some(Token::Ident(ident)), line, column),
+                    "`rule`",
+                ))
+            },
+        }
+
+        let (name, ..) = self.ident("rule name")?;
+        self.expect(Token::OpenBrace)?;
+
+        // The outcome is only known once the criteria have been parsed
+        let mut rule = Rule::named(name, "");
+
+        while self.peek()? != Some(Token::CloseBrace) {
+            self.criterion(&mut rule)?;
+
+            if self.peek()? != Some(Token::CloseBrace) {
+                self.expect(Token::Semicolon)?;
+            }
+        }
+
+        self.expect(Token::CloseBrace)?;
+        self.expect(Token::Arrow)?;
+
+        rule.outcome = match self.next()? {
+            (Some(Token::Text(outcome)), ..) => outcome,
+            spanned => return Err(Self::unexpected(spanned, "outcome text")),
+        };
+
+        if self.peek()? == Some(Token::Semicolon) {
+            self.next()?;
+        }
+
+        Ok(rule)
+    }
+
+    fn criterion(&mut self, rule: &mut DslRule<'a>) -> Result<(), ParseError> {
+        let (fact, line, column) = self.ident("fact name or `}`")?;
+
+        let operator = match self.next()? {
+            (Some(Token::Operator(operator)), ..) => operator,
+            spanned => return Err(Self::unexpected(spanned, "comparison operator")),
+        };
+
+        let error = |message: String| ParseError {
+            line,
+            column,
+            message,
+        };
+
+        let evaluator = match (self.next()?, operator) {
+            ((Some(Token::Number(x)), ..), Operator::Equal) => FloatEvaluator::EqualTo(x).into(),
+            ((Some(Token::Number(x)), ..), Operator::NotEqual) => {
+                FloatEvaluator::NotEqualTo(x).into()
+            },
+            ((Some(Token::Number(x)), ..), Operator::Less) => FloatEvaluator::lt(x).into(),
+            ((Some(Token::Number(x)), ..), Operator::LessEqual) => FloatEvaluator::lte(x).into(),
+            ((Some(Token::Number(x)), ..), Operator::Greater) => FloatEvaluator::gt(x).into(),
+            ((Some(Token::Number(x)), ..), Operator::GreaterEqual) => FloatEvaluator::gte(x).into(),
+            ((Some(Token::Text(text)), ..), Operator::Equal) => ValueEvaluator::Text(text),
+            ((Some(Token::Text(text)), ..), Operator::NotEqual) => ValueEvaluator::NotText(text),
+            ((Some(Token::Ident(ident @ ("true" | "false"))), ..), Operator::Equal) => {
+                ValueEvaluator::Bool(ident == "true")
+            },
+            ((Some(Token::Ident(ident @ ("true" | "false"))), ..), Operator::NotEqual) => {
+                ValueEvaluator::Bool(ident != "true")
+            },
+            ((Some(Token::Text(_) | Token::Ident("true" | "false")), ..), operator) => {
+                return Err(error(format!(
+                    "`{}` can only be used to compare numbers",
+                    operator
+                )))
+            },
+            (spanned, _) => {
+                return Err(Self::unexpected(spanned, "number, text, `true` or `false`"))
+            },
+        };
+
+        let evaluator = match rule.evaluators.get(fact) {
+            Some(existing) => combine(*existing, evaluator).ok_or_else(|| {
+                error(format!(
+                    "fact `{}` already has a criterion in rule `{}` (only a lower and upper bound can be combined)",
+                    fact,
+                    rule.name.as_deref().unwrap_or_default()
+                ))
+            })?,
+            None => evaluator,
+        };
+
+        rule.insert(fact, evaluator);
+
+        Ok(())
+    }
+}
+
+impl From<FloatEvaluator> for ValueEvaluator<'_> {
+    fn from(evaluator: FloatEvaluator) -> Self { Self::Number(evaluator) }
+}
+
+/// Combines a lower bound and an upper bound on the same fact into a range.
+fn combine<'a>(a: ValueEvaluator<'a>, b: ValueEvaluator<'a>) -> Option<ValueEvaluator<'a>> {
+    let range = |lower: FloatRangeBound, upper: FloatRangeBound| {
+        Some(ValueEvaluator::Number(FloatEvaluator::InRange(
+            lower, upper,
+        )))
+    };
+
+    match (a, b) {
+        (
+            ValueEvaluator::Number(FloatEvaluator::GreaterThan(lower)),
+            ValueEvaluator::Number(FloatEvaluator::LessThan(upper)),
+        )
+        | (
+            ValueEvaluator::Number(FloatEvaluator::LessThan(upper)),
+            ValueEvaluator::Number(FloatEvaluator::GreaterThan(lower)),
+        ) => range(lower, upper),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Query;
+
+    #[test]
+    fn parse_rule() {
+        let rules = parse_rules(
+            r#"
+            rule greet_low_health {
+                concept == "Greet";
+                health < 20;
+                is_hostile != true;
+            } => "You look hurt"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.len(), 1);
+
+        let rule = &rules[0];
+        assert_eq!(rule.name.as_deref(), Some("greet_low_health"));
+        assert_eq!(rule.outcome, "You look hurt");
+        assert_eq!(
+            rule.evaluators.get("concept"),
+            Some(&ValueEvaluator::Text("Greet"))
+        );
+        assert_eq!(
+            rule.evaluators.get("health"),
+            Some(&ValueEvaluator::Number(FloatEvaluator::lt(20.)))
+        );
+        assert_eq!(
+            rule.evaluators.get("is_hostile"),
+            Some(&ValueEvaluator::Bool(false))
+        );
+    }
+
+    #[test]
+    fn parse_ruleset() {
+        let ruleset = parse(
+            r#"
+            # fallback greeting
+            rule greet { concept == "Greet" } => "Hello!";
+            // more specific greeting
+            rule greet_low_health { concept == "Greet"; health < 20; } => "You look hurt"
+            rule anything {} => "..."
+            "#,
+        )
+        .unwrap();
+
+        let mut query = Query::new();
+        query.insert("concept", Value::from("Greet"));
+
+        assert_eq!(ruleset.evaluate(&query).unwrap().outcome, "Hello!");
+
+        query.insert("health", Value::from(-5.5));
+        assert_eq!(ruleset.evaluate(&query).unwrap().outcome, "You look hurt");
+
+        query.insert("concept", Value::from("Farewell"));
+        assert_eq!(ruleset.evaluate(&query).unwrap().outcome, "...");
+    }
+
+    #[test]
+    fn parse_range() {
+        let rules = parse_rules("rule healthy { health >= 5; health < 25; } => \"Fine\"").unwrap();
+
+        assert_eq!(
+            rules[0].evaluators.get("health"),
+            Some(&ValueEvaluator::Number(FloatEvaluator::range(5., 25.)))
+        );
+    }
+
+    #[test]
+    fn mismatched_types_do_not_match() {
+        assert!(!ValueEvaluator::Text("5").evaluate(Value::Number(5.)));
+        assert!(!ValueEvaluator::NotText("5").evaluate(Value::Number(5.)));
+        assert!(!ValueEvaluator::Number(FloatEvaluator::EqualTo(1.)).evaluate(Value::Bool(true)));
+        assert!(ValueEvaluator::NotText("5").evaluate(Value::Text("6")));
+    }
+
+    fn error(source: &str) -> (usize, usize, String) {
+        let error = parse(source).err().unwrap();
+        (error.line, error.column, error.message)
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            error("rule greet {\n  concept = \"Greet\";\n} => \"Hello!\""),
+            (2, 11, "unexpected character `=`".to_string())
+        );
+        assert_eq!(
+            error("rule greet {\n  concept == \"Greet;\n} => \"Hello!\""),
+            (
+                2,
+                14,
+                "unterminated text (text must end with `\"` on the same line)".to_string()
+            )
+        );
+        assert_eq!(
+            error("rule greet { concept < \"Greet\"; } => \"Hello!\""),
+            (1, 14, "`<` can only be used to compare numbers".to_string())
+        );
+        assert_eq!(
+            error("rule greet { health > 5; health > 10; } => \"Hello!\""),
+            (
+                1,
+                26,
+                "fact `health` already has a criterion in rule `greet` (only a lower and upper bound can be combined)".to_string()
+            )
+        );
+        assert_eq!(
+            error("rule greet { health > 5 }"),
+            (1, 26, "expected `=>`, found end of input".to_string())
+        );
+        assert_eq!(
+            error("greet { health > 5 } => \"Hello!\""),
+            (1, 1, "expected `rule`, found `greet`".to_string())
+        );
+        assert_eq!(
+            error("rule greet { health > 5 } => 5"),
+            (1, 30, "expected outcome text, found number `5`".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_error_display() {
+        let error = parse("rule {").err().unwrap();
+        assert_eq!(error.to_string(), "1:6: expected rule name, found `{`");
+    }
+
+    #[test]
+    fn lint_shadowed_text_rule() {
+        let ruleset = parse(
+            r#"
+            rule greet { concept == "Greet"; } => "Hello!"
+            rule greet_player { concept == "Greet"; speaker == "Player"; } => "Hey you!"
+            rule greet_others { concept == "Greet"; speaker != "Player"; } => "Hi!"
+            "#,
+        )
+        .unwrap();
+
+        let lints = crate::lint::lint_ruleset_with(
+            &ruleset,
+            crate::lint::LintOptions {
+                complete_snapshots: true,
+            },
+        );
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].rule(), 0);
+        assert!(!ValueEvaluator::covers(&[
+            ValueEvaluator::Text("Player"),
+            ValueEvaluator::NotText("Enemy")
+        ]));
+    }
+
+    #[test]
+    fn value_evaluator_overlaps() {
+        assert!(ValueEvaluator::Text("Player").overlaps(&ValueEvaluator::NotText("Enemy")));
+        assert!(!ValueEvaluator::Text("Player").overlaps(&ValueEvaluator::NotText("Player")));
+        assert!(!ValueEvaluator::Text("Player").overlaps(&ValueEvaluator::Text("Enemy")));
+        assert!(ValueEvaluator::NotText("Player").overlaps(&ValueEvaluator::NotText("Enemy")));
+        assert!(!ValueEvaluator::Bool(true).overlaps(&ValueEvaluator::Bool(false)));
+        assert!(!ValueEvaluator::Bool(true).overlaps(&ValueEvaluator::Text("true")));
+        assert!(ValueEvaluator::Number(FloatEvaluator::gt(1.))
+            .overlaps(&ValueEvaluator::Number(FloatEvaluator::lt(2.))));
+    }
+}
@@ -7,6 +7,10 @@
 /// You can choose to create your own implementation of the trait, or use the
 /// `FloatEvaluator` implementation (provided by the crate's `float` feature)
 /// that allows you to evaluate floating-point numbers (Rust's `f64` type).
+///
+/// Evaluators used in a `Ruleset` must also implement `Copy`. Implementing
+/// `Hash` and `PartialEq` too allows criteria shared between rules to be
+/// detected (see `Ruleset::with_shared_criteria` and `CriterionCache`).
 pub trait Evaluator<T> {
     /// Evaluates against a value of type `T` and returns true or false based on
     /// the underlying logic.
@@ -0,0 +1,637 @@
+#[cfg(feature = "json")]
+use alloc::{boxed::Box, collections::BTreeMap, string::ToString, vec};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use indexmap::IndexSet;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "json")]
+use serde_json::{Map, Value};
+
+use crate::{evaluator::Evaluator, hash::FactHasher, rule::Rule, ruleset::Ruleset};
+
+/// The version of the ruleset file format written by this version of Mímir.
+///
+/// Version `0` refers to files written before the format was versioned (a
+/// serialized `Ruleset` without a header).
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The header of a `RulesetFile`, describing the ruleset stored in the file.
+#[derive(Debug, Default, PartialEq)]
+pub struct RulesetHeader<FactKey> {
+    /// An optional name that identifies the ruleset (e.g. the name of a mod).
+    pub name: Option<String>,
+    /// The fact keys referred to by the ruleset's rules (every fact checked by
+    /// a rule must be declared when the file is deserialized).
+    pub keys: Vec<FactKey>,
+}
+
+/// A `RulesetFile` is a versioned envelope around a `Ruleset`, intended for
+/// rulesets that are stored as assets (e.g. bundled with your game or loaded
+/// from mods).
+///
+/// Files are (de)serialized as a map containing the file format `version`, an
+/// optional `name`, the fact `keys` used by the ruleset and its `rules`:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "name": "barks",
+///   "keys": ["health"],
+///   "rules": [{ "criteria": { "health": { "lt": 25 } }, "outcome": "Ouch!" }]
+/// }
+/// ```
+///
+/// Deserializing a file written with a different format version (or a file
+/// whose rules check a fact that isn't declared in its `keys`) fails with an
+/// error. When the `json` feature is enabled, files written with older format
+/// versions can be upgraded while loading with `RulesetFile::from_json_value`
+/// (see `Migrations`).
+pub struct RulesetFile<FactKey, FactType, FactEvaluator: Evaluator<FactType>, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    /// The header of the file.
+    pub header: RulesetHeader<FactKey>,
+    /// The ruleset stored in the file.
+    pub ruleset: Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+}
+
+impl<FactKey, FactType, FactEvaluator, Outcome>
+    RulesetFile<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq + Clone,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy,
+{
+    /// Creates a new (unnamed) file for the provided ruleset, declaring every
+    /// fact key referred to by the ruleset's rules in the header.
+    pub fn new(ruleset: Ruleset<FactKey, FactType, FactEvaluator, Outcome>) -> Self {
+        let keys: IndexSet<FactKey, FactHasher> = ruleset
+            .rules()
+            .iter()
+            .flat_map(|rule| rule.evaluators.keys().cloned())
+            .collect();
+
+        Self {
+            header: RulesetHeader {
+                name: None,
+                keys: keys.into_iter().collect(),
+            },
+            ruleset,
+        }
+    }
+
+    /// Sets the name of the ruleset stored in the file.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.header.name = Some(name.into());
+        self
+    }
+}
+
+impl<FactKey, FactType, FactEvaluator, Outcome>
+    RulesetFile<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+    FactEvaluator: Evaluator<FactType>,
+{
+    /// Consumes the file, returning the ruleset stored in it.
+    pub fn into_ruleset(self) -> Ruleset<FactKey, FactType, FactEvaluator, Outcome> { self.ruleset }
+}
+
+/// The serialized representation of a `RulesetFile`.
+#[derive(Serialize)]
+#[serde(rename = "RulesetFile")]
+struct RawFileRef<'a, FactKey, Rules> {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: &'a Option<String>,
+    keys: &'a [FactKey],
+    rules: &'a Rules,
+}
+
+/// The deserialized representation of a `RulesetFile`.
+#[derive(Deserialize)]
+#[serde(rename = "RulesetFile")]
+struct RawFile<FactKey, Rules> {
+    version: u32,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default = "Vec::new")]
+    keys: Vec<FactKey>,
+    rules: Rules,
+}
+
+impl<FactKey, FactType, FactEvaluator, Outcome> Serialize
+    for RulesetFile<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq + Serialize,
+    FactEvaluator: Evaluator<FactType>,
+    Ruleset<FactKey, FactType, FactEvaluator, Outcome>: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawFileRef {
+            version: FORMAT_VERSION,
+            name: &self.header.name,
+            keys: &self.header.keys,
+            rules: &self.ruleset,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, FactKey, FactType, FactEvaluator, Outcome> Deserialize<'de>
+    for RulesetFile<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq + fmt::Debug + Deserialize<'de>,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy,
+    Rule<FactKey, FactType, FactEvaluator, Outcome>: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let file =
+            RawFile::<FactKey, Vec<Rule<FactKey, FactType, FactEvaluator, Outcome>>>::deserialize(
+                deserializer,
+            )?;
+
+        if file.version != FORMAT_VERSION {
+            return Err(de::Error::custom(format_args!(
+                "unsupported ruleset file format version {} (expected {})",
+                file.version, FORMAT_VERSION
+            )));
+        }
+
+        let keys: IndexSet<&FactKey, FactHasher> = file.keys.iter().collect();
+
+        // Rules are checked before they're sorted, so that errors refer to
+        // their position in the file
+        for (position, rule) in file.rules.iter().enumerate() {
+            if let Some(fact) = rule.evaluators.keys().find(|fact| !keys.contains(fact)) {
+                return Err(de::Error::custom(format_args!(
+                    "rule #{} checks fact {:?}, which isn't declared in the file's keys",
+                    position, fact
+                )));
+            }
+        }
+
+        Ok(Self {
+            header: RulesetHeader {
+                name: file.name,
+                keys: file.keys,
+            },
+            ruleset: Ruleset::new(file.rules),
+        })
+    }
+}
+
+/// A function that upgrades a ruleset file (represented as a JSON value) from
+/// one format version to the next.
+#[cfg(feature = "json")]
+pub type Migration = Box<dyn Fn(Value) -> Result<Value, String> + Send + Sync>;
+
+/// A collection of migrations used to upgrade ruleset files written with older
+/// format versions while loading them (see `RulesetFile::from_json_value`).
+///
+/// Each migration upgrades a file from the version it's registered with to
+/// the next version; the file's `version` field is updated automatically. The
+/// default collection contains Mímir's own migrations, and your own can be
+/// added to the collection to run after them (e.g. to upgrade the
+/// representation of a custom evaluator type).
+///
+/// Files in a format other than JSON can be migrated by deserializing them
+/// into a `serde_json::Value` first (as long as the format is self-describing).
+#[cfg(feature = "json")]
+pub struct Migrations {
+    migrations: BTreeMap<u32, Vec<Migration>>,
+}
+
+#[cfg(feature = "json")]
+impl Migrations {
+    /// Creates an empty collection of migrations (without Mímir's own
+    /// migrations).
+    pub fn empty() -> Self {
+        Self {
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a migration that upgrades files from the provided format version to
+    /// the next.
+    pub fn add(
+        mut self,
+        from: u32,
+        migration: impl Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> Self {
+        self.migrations
+            .entry(from)
+            .or_default()
+            .push(Box::new(migration));
+        self
+    }
+
+    /// Upgrades the provided file to the current format version.
+    fn migrate(&self, mut file: Value) -> Result<Value, FileError> {
+        let mut version = match &file {
+            Value::Object(map) => match map.get("version") {
+                Some(version) => version
+                    .as_u64()
+                    .and_then(|version| u32::try_from(version).ok())
+                    .ok_or(FileError::InvalidVersion)?,
+                None => 0,
+            },
+            // Unversioned files are allowed to be a plain list of rules
+            Value::Array(_) => 0,
+            _ => return Err(FileError::InvalidVersion),
+        };
+
+        if version > FORMAT_VERSION {
+            return Err(FileError::UnsupportedVersion(version));
+        }
+
+        while version < FORMAT_VERSION {
+            let migrations = self
+                .migrations
+                .get(&version)
+                .ok_or(FileError::MissingMigration(version))?;
+
+            for migration in migrations {
+                file =
+                    migration(file).map_err(|message| FileError::Migration { version, message })?;
+            }
+
+            version += 1;
+
+            match &mut file {
+                Value::Object(map) => {
+                    map.insert("version".to_string(), Value::from(version));
+                },
+                _ => {
+                    return Err(FileError::Migration {
+                        version: version - 1,
+                        message: "migration didn't produce a map".to_string(),
+                    })
+                },
+            }
+        }
+
+        Ok(file)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Default for Migrations {
+    /// Creates a collection containing Mímir's own migrations.
+    fn default() -> Self { Self::empty().add(0, migrate_unversioned) }
+}
+
+/// Upgrades an unversioned file (a serialized `Ruleset` from before the
+/// readable rule representation) to version 1.
+///
+/// Rules' `evaluators` are renamed to `criteria` (and their unused `marker`
+/// removed), the facts that they check are declared in the file's `keys`, and
+/// any `FloatEvaluator` in its previous representation (e.g.
+/// `{"InRange": [{"Inclusive": 5.0}, {"Exclusive": 25.0}]}`) is rewritten to
+/// its current representation.
+#[cfg(feature = "json")]
+fn migrate_unversioned(file: Value) -> Result<Value, String> {
+    let rules = match file {
+        Value::Array(rules) => rules,
+        Value::Object(mut map) => match map.remove("rules") {
+            Some(Value::Array(rules)) => rules,
+            _ => return Err("expected a list of rules".to_string()),
+        },
+        _ => return Err("expected a list of rules".to_string()),
+    };
+
+    let mut keys = Vec::new();
+
+    let rules = rules
+        .into_iter()
+        .map(|rule| {
+            let Value::Object(mut rule) = rule else {
+                return Err("expected a rule to be a map".to_string());
+            };
+
+            rule.remove("marker");
+
+            if let Some(Value::Object(evaluators)) = rule.remove("evaluators") {
+                let criteria = evaluators
+                    .into_iter()
+                    .map(|(fact, evaluator)| (fact, migrate_float_evaluator(evaluator)))
+                    .collect();
+                rule.insert("criteria".to_string(), Value::Object(criteria));
+            }
+
+            if let Some(Value::Object(criteria)) = rule.get("criteria") {
+                for fact in criteria.keys() {
+                    let fact = Value::String(fact.clone());
+                    if !keys.contains(&fact) {
+                        keys.push(fact);
+                    }
+                }
+            }
+
+            Ok(Value::Object(rule))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut file = Map::new();
+    file.insert("keys".to_string(), Value::Array(keys));
+    file.insert("rules".to_string(), Value::Array(rules));
+    Ok(Value::Object(file))
+}
+
+/// Rewrites a `FloatEvaluator` from its unversioned representation, returning
+/// any other value unchanged.
+#[cfg(feature = "json")]
+fn migrate_float_evaluator(evaluator: Value) -> Value {
+    fn bound(bound: &Value, exclusive: &str, inclusive: &str) -> Option<(String, Value)> {
+        let Value::Object(bound) = bound else {
+            return None;
+        };
+
+        match bound.iter().next() {
+            Some((kind, x)) if bound.len() == 1 && x.is_number() => match kind.as_str() {
+                "Exclusive" => Some((exclusive.to_string(), x.clone())),
+                "Inclusive" => Some((inclusive.to_string(), x.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    let migrated = match &evaluator {
+        Value::Object(map) if map.len() == 1 => {
+            let (variant, value) = map.iter().next().unwrap();
+
+            match (variant.as_str(), value) {
+                ("EqualTo", x) if x.is_number() => Some(vec![("eq".to_string(), x.clone())]),
+                ("NotEqualTo", x) if x.is_number() => Some(vec![("ne".to_string(), x.clone())]),
+                ("LessThan", upper) => bound(upper, "lt", "lte").map(|upper| vec![upper]),
+                ("GreaterThan", lower) => bound(lower, "gt", "gte").map(|lower| vec![lower]),
+                ("InRange", Value::Array(bounds)) if bounds.len() == 2 => {
+                    bound(&bounds[0], "gt", "gte")
+                        .zip(bound(&bounds[1], "lt", "lte"))
+                        .map(|(lower, upper)| vec![lower, upper])
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    };
+
+    match migrated {
+        Some(comparisons) => Value::Object(comparisons.into_iter().collect()),
+        None => evaluator,
+    }
+}
+
+/// An error that occurred while loading a `RulesetFile` with
+/// `RulesetFile::from_json_value`.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub enum FileError {
+    /// The file's `version` field isn't a valid format version.
+    InvalidVersion,
+    /// The file was written with a newer (unknown) format version.
+    UnsupportedVersion(u32),
+    /// There's no migration from the provided format version to the next.
+    MissingMigration(u32),
+    /// A migration from the provided format version failed.
+    Migration {
+        /// The format version that the migration was upgrading from.
+        version: u32,
+        /// A description of the error.
+        message: String,
+    },
+    /// The (migrated) file doesn't match the current format.
+    Invalid(serde_json::Error),
+}
+
+#[cfg(feature = "json")]
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidVersion => write!(f, "invalid ruleset file format version"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported ruleset file format version {} (expected at most {})",
+                version, FORMAT_VERSION
+            ),
+            Self::MissingMigration(version) => write!(
+                f,
+                "no migration from ruleset file format version {} to {}",
+                version,
+                version + 1
+            ),
+            Self::Migration { version, message } => write!(
+                f,
+                "failed to migrate from ruleset file format version {}: {}",
+                version, message
+            ),
+            Self::Invalid(error) => write!(f, "invalid ruleset file: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<FactKey, FactType, FactEvaluator, Outcome>
+    RulesetFile<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq + fmt::Debug + de::DeserializeOwned,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy,
+    Rule<FactKey, FactType, FactEvaluator, Outcome>: de::DeserializeOwned,
+{
+    /// Loads a file from the provided JSON value, upgrading it to the current
+    /// format version with the provided migrations if it was written with an
+    /// older version.
+    ///
+    /// ```
+    /// # #[cfg(feature = "float")] {
+    /// use subtale_mimir::{
+    ///     file::{Migrations, RulesetFile},
+    ///     prelude::*,
+    /// };
+    ///
+    /// // An unversioned ruleset (serialized before the format was versioned)
+    /// let json = serde_json::json!([{
+    ///     "marker": null,
+    ///     "evaluators": { "enemies_killed": { "EqualTo": 5.0 } },
+    ///     "outcome": "You killed 5 enemies!"
+    /// }]);
+    ///
+    /// let file: RulesetFile<String, f64, FloatEvaluator, String> =
+    ///     RulesetFile::from_json_value(json, &Migrations::default()).unwrap();
+    ///
+    /// let mut query = Query::new();
+    /// query.insert("enemies_killed".to_string(), 5.);
+    ///
+    /// assert_eq!(
+    ///     file.ruleset.evaluate(&query).unwrap().outcome,
+    ///     "You killed 5 enemies!"
+    /// );
+    /// # }
+    /// ```
+    pub fn from_json_value(value: Value, migrations: &Migrations) -> Result<Self, FileError> {
+        let file = migrations.migrate(value)?;
+        Ok(serde_json::from_value(file)?)
+    }
+
+    /// Loads a file from the provided JSON string (see
+    /// `RulesetFile::from_json_value`).
+    pub fn from_json_str(json: &str, migrations: &Migrations) -> Result<Self, FileError> {
+        let value = serde_json::from_str(json)?;
+        Self::from_json_value(value, migrations)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for FileError {
+    fn from(error: serde_json::Error) -> Self { Self::Invalid(error) }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    type FloatFile = RulesetFile<String, f64, FloatEvaluator, String>;
+
+    fn ruleset() -> Ruleset<String, f64, FloatEvaluator, String> {
+        let mut rule = Rule::new("You killed 5 enemies!".to_string());
+        rule.insert("enemies_killed".to_string(), FloatEvaluator::EqualTo(5.));
+
+        let mut more_specific_rule = Rule::named(
+            "doors",
+            "You killed 5 enemies and opened 2 doors!".to_string(),
+        );
+        more_specific_rule.insert("enemies_killed".to_string(), FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened".to_string(), FloatEvaluator::gt(2.));
+
+        Ruleset::new(vec![rule, more_specific_rule])
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let file = RulesetFile::new(ruleset()).with_name("test");
+        assert_eq!(file.header.keys, ["enemies_killed", "doors_opened"]);
+
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(json.starts_with(
+            r#"{"version":1,"name":"test","keys":["enemies_killed","doors_opened"],"rules":["#
+        ));
+
+        let loaded: FloatFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.header, file.header);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    }
+
+    #[test]
+    fn file_version_mismatch() {
+        let error = serde_json::from_str::<FloatFile>(r#"{"version":2,"rules":[]}"#)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .starts_with("unsupported ruleset file format version 2 (expected 1)"));
+
+        assert!(serde_json::from_str::<FloatFile>(r#"{"rules":[]}"#).is_err());
+    }
+
+    #[test]
+    fn file_undeclared_keys() {
+        let json = r#"{
+            "version": 1,
+            "keys": ["health"],
+            "rules": [
+                { "criteria": { "health": { "lt": 25 } }, "outcome": "Ouch!" },
+                { "criteria": { "health": { "lt": 25 }, "mana": { "eq": 0 } }, "outcome": "Empty" }
+            ]
+        }"#;
+
+        let error = serde_json::from_str::<FloatFile>(json).err().unwrap();
+        assert!(error
+            .to_string()
+            .starts_with(r#"rule #1 checks fact "mana", which isn't declared in the file's keys"#));
+
+        // Keys that aren't checked by any rule are allowed
+        let json = r#"{"version":1,"keys":["health","mana"],"rules":[{"criteria":{"health":{"lt":25}},"outcome":"Ouch!"}]}"#;
+        assert!(serde_json::from_str::<FloatFile>(json).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn file_migrations() {
+        let unversioned = serde_json::json!({
+            "rules": [
+                {
+                    "marker": null,
+                    "evaluators": {
+                        "health": {
+                            "InRange": [{ "Inclusive": 5.0 }, { "Exclusive": 25.0 }]
+                        },
+                        "doors_opened": { "GreaterThan": { "Exclusive": 2.0 } }
+                    },
+                    "outcome": "Hurt"
+                },
+                {
+                    "marker": null,
+                    "evaluators": { "health": { "NotEqualTo": 0.0 } },
+                    "outcome": "Alive"
+                }
+            ]
+        });
+
+        let file = FloatFile::from_json_value(unversioned.clone(), &Migrations::default()).unwrap();
+        assert_eq!(file.header.keys, ["doors_opened", "health"]);
+        let rules = file.ruleset.rules();
+        assert_eq!(rules[0].outcome, "Hurt");
+        assert_eq!(
+            rules[0].evaluators.get("health"),
+            Some(&FloatEvaluator::range(5., 25.))
+        );
+        assert_eq!(
+            rules[0].evaluators.get("doors_opened"),
+            Some(&FloatEvaluator::gt(2.))
+        );
+        assert_eq!(
+            rules[1].evaluators.get("health"),
+            Some(&FloatEvaluator::NotEqualTo(0.))
+        );
+
+        assert!(matches!(
+            FloatFile::from_json_value(unversioned, &Migrations::empty()),
+            Err(FileError::MissingMigration(0))
+        ));
+
+        // Current files are loaded without migrating
+        let json = serde_json::to_string(&RulesetFile::new(ruleset())).unwrap();
+        assert!(FloatFile::from_json_str(&json, &Migrations::empty()).is_ok());
+
+        assert!(matches!(
+            FloatFile::from_json_str(r#"{"version":2,"rules":[]}"#, &Migrations::default()),
+            Err(FileError::UnsupportedVersion(2))
+        ));
+
+        let failing = Migrations::empty().add(0, |_| Err("oops".to_string()));
+        assert_eq!(
+            FloatFile::from_json_str("[]", &failing)
+                .err()
+                .unwrap()
+                .to_string(),
+            "failed to migrate from ruleset file format version 0: oops"
+        );
+    }
+}
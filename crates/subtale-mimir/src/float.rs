@@ -1,8 +1,19 @@
-use float_cmp::approx_eq;
+use alloc::vec::Vec;
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use float_cmp::{approx_eq, ApproxEqUlps, F64Margin, Ulps};
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-use super::evaluator::Evaluator;
+use super::{
+    evaluator::Evaluator,
+    lint::Analyze,
+    schema::{Typed, ValueType},
+};
 
 /// Represents a bound of a range used during float comparisons made by
 /// `FloatEvaluator`.
@@ -15,10 +26,98 @@ pub enum FloatRangeBound {
     Inclusive(f64),
 }
 
+/// The tolerance used to compare `f64` values for equality by
+/// `FloatEvaluator::EqualWithin` and `FloatEvaluator::NotEqualWithin`.
+///
+/// A negative (or NaN) tolerance is invalid: it only considers identical
+/// values equal (as if it were zero), and is rejected when deserialized (see
+/// `FloatTolerance::is_valid`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "CompactFloatTolerance"))]
+pub enum FloatTolerance {
+    /// Values are equal if the absolute difference between them is at most the
+    /// contained value (e.g. `0.01`).
+    Absolute(f64),
+    /// Values are equal if they're at most the contained number of
+    /// representable `f64` values apart (units in the last place).
+    Ulps(i64),
+}
+
+impl FloatTolerance {
+    /// Returns `true` if the tolerance isn't negative (or NaN).
+    pub fn is_valid(self) -> bool {
+        match self {
+            Self::Absolute(epsilon) => epsilon >= 0.,
+            Self::Ulps(ulps) => ulps >= 0,
+        }
+    }
+
+    /// Returns `true` if the provided values are equal within the tolerance.
+    ///
+    /// Identical values (including infinities) are always equal, whereas NaN
+    /// is never equal to anything.
+    pub fn equal(self, a: f64, b: f64) -> bool {
+        a == b
+            || (!a.is_nan()
+                && !b.is_nan()
+                && match self {
+                    Self::Absolute(epsilon) => {
+                        // `f64::abs` isn't available in `core` (for `no_std`)
+                        let difference = a - b;
+                        (if difference < 0. {
+                            -difference
+                        } else {
+                            difference
+                        }) <= epsilon
+                    },
+                    Self::Ulps(ulps) => a.approx_eq_ulps(&b, ulps),
+                })
+    }
+}
+
+/// Hashes an `f64` consistently with its `PartialEq` implementation (so that
+/// `0.0` and `-0.0` have the same hash).
+fn hash_f64<H: Hasher>(value: f64, state: &mut H) {
+    let value = if value == 0. { 0. } else { value };
+    value.to_bits().hash(state);
+}
+
+impl Hash for FloatRangeBound {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Self::Exclusive(x) | Self::Inclusive(x) => hash_f64(*x, state),
+        }
+    }
+}
+
+impl Hash for FloatTolerance {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Self::Absolute(epsilon) => hash_f64(*epsilon, state),
+            Self::Ulps(ulps) => ulps.hash(state),
+        }
+    }
+}
+
 /// A reference implementation of the `Evaluator` trait that allows for
 /// comparisons against facts with a value type of `f64`.
+///
+/// A fact whose value is NaN never satisfies an evaluator, except for
+/// `NotEqualTo` and `NotEqualWithin` (as NaN isn't equal to any value).
+/// Likewise, `EqualTo(f64::NAN)` never matches and `NotEqualTo(f64::NAN)`
+/// always matches.
+///
+/// When the `serde` feature is enabled, evaluators are (de)serialized as a map
+/// of comparison operators (`eq`, `ne`, `lt`, `lte`, `gt` and `gte`) to
+/// values, e.g. `{"gte": 5.0, "lt": 25.0}` for
+/// `FloatEvaluator::range(5., 25.)`. Formats that aren't human-readable (e.g.
+/// Mímir's binary format) use a compact representation instead.
 #[derive(Clone, Copy, Debug, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FloatEvaluator {
     /// Checks if a fact has a specific `f64` value (using the `float_cmp` crate
     /// for approximate equality).
@@ -26,6 +125,12 @@ pub enum FloatEvaluator {
     /// Checks if a fact does not have a specific `f64` value (using the
     /// `float_cmp` crate for approximate equality).
     NotEqualTo(f64),
+    /// Checks if a fact has a specific `f64` value, within the provided
+    /// tolerance.
+    EqualWithin(f64, FloatTolerance),
+    /// Checks if a fact does not have a specific `f64` value, within the
+    /// provided tolerance.
+    NotEqualWithin(f64, FloatTolerance),
     /// Checks if a fact's value is less than a given `f64` (see
     /// `FloatRangeBound` for guidance on inclusive/exclusive bounds).
     LessThan(FloatRangeBound),
@@ -40,8 +145,10 @@ pub enum FloatEvaluator {
 impl Evaluator<f64> for FloatEvaluator {
     fn evaluate(self, value: f64) -> bool {
         match self {
-            Self::EqualTo(x) => approx_eq!(f64, x, value),
-            Self::NotEqualTo(x) => !approx_eq!(f64, x, value),
+            Self::EqualTo(x) => !value.is_nan() && approx_eq!(f64, x, value),
+            Self::NotEqualTo(x) => value.is_nan() || !approx_eq!(f64, x, value),
+            Self::EqualWithin(x, tolerance) => tolerance.equal(x, value),
+            Self::NotEqualWithin(x, tolerance) => !tolerance.equal(x, value),
             Self::LessThan(upper) => match upper {
                 FloatRangeBound::Exclusive(x) => value < x,
                 FloatRangeBound::Inclusive(x) => value <= x,
@@ -68,6 +175,77 @@ impl Evaluator<f64> for FloatEvaluator {
     }
 }
 
+impl Hash for FloatEvaluator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Self::EqualTo(x) | Self::NotEqualTo(x) => hash_f64(*x, state),
+            Self::EqualWithin(x, tolerance) | Self::NotEqualWithin(x, tolerance) => {
+                hash_f64(*x, state);
+                tolerance.hash(state);
+            },
+            Self::LessThan(bound) | Self::GreaterThan(bound) => bound.hash(state),
+            Self::InRange(lower, upper) => {
+                lower.hash(state);
+                upper.hash(state);
+            },
+        }
+    }
+}
+
+impl Typed for FloatEvaluator {
+    fn value_type(&self) -> ValueType { ValueType::Number }
+}
+
+/// Analysis ignores NaN fact values (see `FloatEvaluator::interval`).
+impl Analyze for FloatEvaluator {
+    fn is_satisfiable(&self) -> bool { self.intervals().iter().any(|x| !x.is_empty()) }
+
+    fn implies(&self, other: &Self) -> bool {
+        // The other evaluator's intervals are separated by a gap (if they're
+        // both non-empty), so each of this evaluator's intervals must be
+        // contained in one of them
+        let others = other.intervals();
+
+        self.intervals()
+            .iter()
+            .all(|interval| others.iter().any(|other| interval.is_subset(other)))
+    }
+
+    fn covers(evaluators: &[Self]) -> bool {
+        let mut intervals: Vec<_> = evaluators
+            .iter()
+            .flat_map(|evaluator| evaluator.intervals())
+            .collect();
+
+        // Merge the intervals from left to right: a gap between them can't be
+        // filled by a later interval
+        intervals.sort_unstable_by(|a, b| FloatInterval::compare_lower(a.lower, b.lower));
+
+        let mut covered = FloatInterval::EMPTY;
+
+        for interval in intervals {
+            match covered.union(&interval) {
+                Some(union) => covered = union,
+                None => return false,
+            }
+        }
+
+        covered == FloatInterval::ALL
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        let others = other.intervals();
+
+        self.intervals().iter().any(|interval| {
+            others
+                .iter()
+                .any(|other| !interval.intersection(other).is_empty())
+        })
+    }
+}
+
 impl FloatEvaluator {
     /// Utility function for composing an instance of `FloatEvaluator` that
     /// checks for values less than `value`.
@@ -95,11 +273,591 @@ impl FloatEvaluator {
             FloatRangeBound::Exclusive(upper),
         )
     }
+
+    /// Returns the interval of values accepted by the evaluator, or `None` for
+    /// `NotEqualTo` and `NotEqualWithin` (which accept every value outside of
+    /// an interval).
+    ///
+    /// `EqualTo` and `EqualWithin` are represented by the interval of values
+    /// that they consider equal (within their tolerance), e.g. the values at
+    /// most 4 ULPs away from `EqualTo`'s value.
+    pub fn interval(&self) -> Option<FloatInterval> {
+        match *self {
+            Self::EqualTo(x) => Some(Self::approx_interval(x)),
+            Self::EqualWithin(x, tolerance) => Some(Self::tolerance_interval(x, tolerance)),
+            Self::NotEqualTo(_) | Self::NotEqualWithin(..) => None,
+            Self::LessThan(upper) => Some(FloatInterval::new(None, Some(upper))),
+            Self::GreaterThan(lower) => Some(FloatInterval::new(Some(lower), None)),
+            Self::InRange(lower, upper) => Some(FloatInterval::new(Some(lower), Some(upper))),
+        }
+    }
+
+    /// Returns the interval of values considered equal to `x` by `EqualTo`
+    /// (using `float_cmp`'s default margin).
+    fn approx_interval(x: f64) -> FloatInterval {
+        let margin = F64Margin::default();
+        let (lower, upper) = Self::epsilon_bounds(x, margin.epsilon);
+        let (ulps_lower, ulps_upper) = Self::ulps_bounds(x, margin.ulps);
+
+        Self::equal_interval(x, lower.min(ulps_lower), upper.max(ulps_upper), |value| {
+            !value.is_nan() && approx_eq!(f64, x, value)
+        })
+    }
+
+    /// Returns the interval of values considered equal to `x` by the provided
+    /// tolerance.
+    fn tolerance_interval(x: f64, tolerance: FloatTolerance) -> FloatInterval {
+        let (lower, upper) = match tolerance {
+            FloatTolerance::Absolute(epsilon) => Self::epsilon_bounds(x, epsilon),
+            FloatTolerance::Ulps(ulps) => Self::ulps_bounds(x, ulps),
+        };
+
+        Self::equal_interval(x, lower, upper, |value| tolerance.equal(x, value))
+    }
+
+    /// Estimates the bounds of the values at most `epsilon` away from `x`.
+    fn epsilon_bounds(x: f64, epsilon: f64) -> (f64, f64) {
+        // Negative and NaN tolerances only consider identical values equal
+        if epsilon.partial_cmp(&0.) != Some(Ordering::Greater) {
+            return (x, x);
+        }
+
+        // An infinite value and tolerance leave the bounds undefined, but every
+        // value is within an infinite tolerance
+        let lower = x - epsilon;
+        let upper = x + epsilon;
+
+        (
+            if lower.is_nan() {
+                f64::NEG_INFINITY
+            } else {
+                lower
+            },
+            if upper.is_nan() { f64::INFINITY } else { upper },
+        )
+    }
+
+    /// Returns the bounds of the values at most `ulps` representable values
+    /// away from `x` (with the same sign).
+    fn ulps_bounds(x: f64, ulps: i64) -> (f64, f64) {
+        const SIGN: u64 = 1 << 63;
+        const INFINITY: i64 = 0x7FF0_0000_0000_0000;
+
+        // The bits of a value's magnitude are ordered in the same way as the
+        // magnitude itself (up to infinity)
+        let magnitude = (x.to_bits() & !SIGN) as i64;
+        let ulps = ulps.max(0);
+        let nearest = f64::from_bits(magnitude.saturating_sub(ulps).max(0) as u64);
+        let furthest = f64::from_bits(magnitude.saturating_add(ulps).min(INFINITY) as u64);
+
+        if x.is_sign_negative() {
+            (-furthest, -nearest)
+        } else {
+            (nearest, furthest)
+        }
+    }
+
+    /// Returns the interval of values accepted by `equal`, which must accept
+    /// an interval of values around `x` (or no values).
+    ///
+    /// The estimated bounds are corrected by stepping between adjacent values,
+    /// as they might be a few values off after rounding.
+    fn equal_interval(
+        x: f64,
+        lower: f64,
+        upper: f64,
+        equal: impl Fn(f64) -> bool,
+    ) -> FloatInterval {
+        if !equal(x) {
+            return FloatInterval::EMPTY;
+        }
+
+        let (mut lower, mut upper) = (lower, upper);
+
+        while !equal(lower) {
+            lower = lower.next();
+        }
+        while lower != f64::NEG_INFINITY && equal(lower.prev()) {
+            lower = lower.prev();
+        }
+
+        while !equal(upper) {
+            upper = upper.prev();
+        }
+        while upper != f64::INFINITY && equal(upper.next()) {
+            upper = upper.next();
+        }
+
+        FloatInterval::new(
+            Some(FloatRangeBound::Inclusive(lower)),
+            Some(FloatRangeBound::Inclusive(upper)),
+        )
+    }
+
+    /// Returns the disjoint intervals of values accepted by the evaluator (in
+    /// ascending order).
+    fn intervals(&self) -> [FloatInterval; 2] {
+        let excluded = match *self {
+            Self::NotEqualTo(x) => Self::approx_interval(x),
+            Self::NotEqualWithin(x, tolerance) => Self::tolerance_interval(x, tolerance),
+            _ => return [self.interval().unwrap(), FloatInterval::EMPTY],
+        };
+
+        excluded.complement()
+    }
+}
+
+impl FloatRangeBound {
+    fn value(self) -> f64 {
+        match self {
+            Self::Exclusive(x) | Self::Inclusive(x) => x,
+        }
+    }
+
+    fn is_inclusive(self) -> bool { matches!(self, Self::Inclusive(_)) }
+}
+
+/// A (possibly empty) interval of `f64` values, representing the values
+/// accepted by a `FloatEvaluator` (see `FloatEvaluator::interval`).
+///
+/// A missing bound is unbounded. Intervals are normalized when they're
+/// created: inclusive bounds at infinity are unbounded, and every empty
+/// interval (including those with a NaN bound, which no value satisfies) is
+/// equal to `FloatInterval::EMPTY`.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let low = FloatEvaluator::lt(25.).interval().unwrap();
+/// let mid = FloatEvaluator::range(10., 50.).interval().unwrap();
+/// let high = FloatEvaluator::gte(50.).interval().unwrap();
+///
+/// assert!(low.overlaps(&mid));
+/// assert!(!low.overlaps(&high));
+/// assert_eq!(mid.union(&high), FloatEvaluator::gte(10.).interval());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatInterval {
+    lower: Option<FloatRangeBound>,
+    upper: Option<FloatRangeBound>,
+}
+
+impl FloatInterval {
+    /// The interval containing every value.
+    pub const ALL: FloatInterval = FloatInterval {
+        lower: None,
+        upper: None,
+    };
+    /// The interval containing no values.
+    pub const EMPTY: FloatInterval = FloatInterval {
+        lower: Some(FloatRangeBound::Exclusive(0.)),
+        upper: Some(FloatRangeBound::Exclusive(0.)),
+    };
+
+    /// Creates a new interval with the provided bounds (where `None` is
+    /// unbounded).
+    pub fn new(lower: Option<FloatRangeBound>, upper: Option<FloatRangeBound>) -> Self {
+        let lower = match lower {
+            Some(FloatRangeBound::Inclusive(x)) if x == f64::NEG_INFINITY => None,
+            lower => lower,
+        };
+
+        let upper = match upper {
+            Some(FloatRangeBound::Inclusive(x)) if x == f64::INFINITY => None,
+            upper => upper,
+        };
+
+        let empty = match (lower, upper) {
+            (Some(lower), Some(upper)) => {
+                !(lower.value() < upper.value()
+                    || (lower.value() == upper.value()
+                        && lower.is_inclusive()
+                        && upper.is_inclusive()))
+            },
+            (Some(lower), None) => {
+                lower.value().is_nan() || lower == FloatRangeBound::Exclusive(f64::INFINITY)
+            },
+            (None, Some(upper)) => {
+                upper.value().is_nan() || upper == FloatRangeBound::Exclusive(f64::NEG_INFINITY)
+            },
+            (None, None) => false,
+        };
+
+        if empty {
+            Self::EMPTY
+        } else {
+            Self { lower, upper }
+        }
+    }
+
+    /// Creates a new interval containing a single value.
+    pub fn point(x: f64) -> Self {
+        Self::new(
+            Some(FloatRangeBound::Inclusive(x)),
+            Some(FloatRangeBound::Inclusive(x)),
+        )
+    }
+
+    /// Returns the lower bound of the interval (or `None` if it's unbounded).
+    pub fn lower(&self) -> Option<FloatRangeBound> { self.lower }
+
+    /// Returns the upper bound of the interval (or `None` if it's unbounded).
+    pub fn upper(&self) -> Option<FloatRangeBound> { self.upper }
+
+    /// Returns `true` if the interval doesn't contain any values.
+    pub fn is_empty(&self) -> bool { *self == Self::EMPTY }
+
+    /// Returns `true` if the interval contains the provided value.
+    pub fn contains(&self, x: f64) -> bool {
+        let above = match self.lower {
+            Some(FloatRangeBound::Exclusive(lower)) => x > lower,
+            Some(FloatRangeBound::Inclusive(lower)) => x >= lower,
+            None => !x.is_nan(),
+        };
+
+        let below = match self.upper {
+            Some(FloatRangeBound::Exclusive(upper)) => x < upper,
+            Some(FloatRangeBound::Inclusive(upper)) => x <= upper,
+            None => !x.is_nan(),
+        };
+
+        above && below
+    }
+
+    /// Returns `true` if every value in this interval is also in `other`.
+    pub fn is_subset(&self, other: &FloatInterval) -> bool {
+        self.is_empty()
+            || (Self::compare_lower(other.lower, self.lower).is_le()
+                && Self::compare_upper(other.upper, self.upper).is_ge())
+    }
+
+    /// Returns the interval of values contained in both this interval and
+    /// `other`.
+    pub fn intersection(&self, other: &FloatInterval) -> FloatInterval {
+        if self.is_empty() || other.is_empty() {
+            return Self::EMPTY;
+        }
+
+        let lower = match Self::compare_lower(self.lower, other.lower) {
+            Ordering::Less => other.lower,
+            _ => self.lower,
+        };
+
+        let upper = match Self::compare_upper(self.upper, other.upper) {
+            Ordering::Greater => other.upper,
+            _ => self.upper,
+        };
+
+        Self::new(lower, upper)
+    }
+
+    /// Returns `true` if at least one value is contained in both this interval
+    /// and `other`.
+    pub fn overlaps(&self, other: &FloatInterval) -> bool { !self.intersection(other).is_empty() }
+
+    /// Returns the interval of values contained in either this interval or
+    /// `other`, or `None` if there's a gap between them (so their union isn't
+    /// an interval).
+    pub fn union(&self, other: &FloatInterval) -> Option<FloatInterval> {
+        if self.is_empty() {
+            return Some(*other);
+        }
+
+        if other.is_empty() {
+            return Some(*self);
+        }
+
+        let (first, second) = match Self::compare_lower(self.lower, other.lower) {
+            Ordering::Greater => (other, self),
+            _ => (self, other),
+        };
+
+        let connected = match (first.upper, second.lower) {
+            (Some(upper), Some(lower)) => {
+                lower.value() < upper.value()
+                    || (lower.value() == upper.value()
+                        && (lower.is_inclusive() || upper.is_inclusive()))
+            },
+            _ => true,
+        };
+
+        let upper = match Self::compare_upper(first.upper, second.upper) {
+            Ordering::Less => second.upper,
+            _ => first.upper,
+        };
+
+        connected.then(|| Self::new(first.lower, upper))
+    }
+
+    /// Returns the (disjoint) intervals of values below and above this
+    /// interval.
+    fn complement(&self) -> [FloatInterval; 2] {
+        if self.is_empty() {
+            return [Self::ALL, Self::EMPTY];
+        }
+
+        let flip = |bound: FloatRangeBound| match bound {
+            FloatRangeBound::Exclusive(x) => FloatRangeBound::Inclusive(x),
+            FloatRangeBound::Inclusive(x) => FloatRangeBound::Exclusive(x),
+        };
+
+        [
+            self.lower
+                .map_or(Self::EMPTY, |lower| Self::new(None, Some(flip(lower)))),
+            self.upper
+                .map_or(Self::EMPTY, |upper| Self::new(Some(flip(upper)), None)),
+        ]
+    }
+
+    /// Orders lower bounds by the first value they admit (where `None` admits
+    /// every value).
+    fn compare_lower(a: Option<FloatRangeBound>, b: Option<FloatRangeBound>) -> Ordering {
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a), Some(b)) => a
+                .value()
+                .total_cmp(&b.value())
+                .then(b.is_inclusive().cmp(&a.is_inclusive())),
+        }
+    }
+
+    /// Orders upper bounds by the last value they admit (where `None` admits
+    /// every value).
+    fn compare_upper(a: Option<FloatRangeBound>, b: Option<FloatRangeBound>) -> Ordering {
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a
+                .value()
+                .total_cmp(&b.value())
+                .then(a.is_inclusive().cmp(&b.is_inclusive())),
+        }
+    }
+}
+
+/// The compact (de)serialized representation of a `FloatEvaluator`, used by
+/// formats that aren't human-readable.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "FloatEvaluator")]
+enum CompactFloatEvaluator {
+    EqualTo(f64),
+    NotEqualTo(f64),
+    EqualWithin(f64, FloatTolerance),
+    NotEqualWithin(f64, FloatTolerance),
+    LessThan(FloatRangeBound),
+    GreaterThan(FloatRangeBound),
+    InRange(FloatRangeBound, FloatRangeBound),
+}
+
+/// The (de)serialized representation of a `FloatTolerance`, which is validated
+/// when it's deserialized.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "FloatTolerance")]
+enum CompactFloatTolerance {
+    Absolute(f64),
+    Ulps(i64),
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CompactFloatTolerance> for FloatTolerance {
+    type Error = &'static str;
+
+    fn try_from(tolerance: CompactFloatTolerance) -> Result<Self, Self::Error> {
+        let tolerance = match tolerance {
+            CompactFloatTolerance::Absolute(epsilon) => Self::Absolute(epsilon),
+            CompactFloatTolerance::Ulps(ulps) => Self::Ulps(ulps),
+        };
+
+        if tolerance.is_valid() {
+            Ok(tolerance)
+        } else {
+            Err("a tolerance can't be negative")
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FloatEvaluator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            FloatCriterion::from(*self).serialize(serializer)
+        } else {
+            CompactFloatEvaluator::serialize(self, serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FloatEvaluator {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            FloatCriterion::deserialize(deserializer)?
+                .try_into()
+                .map_err(de::Error::custom)
+        } else {
+            CompactFloatEvaluator::deserialize(deserializer)
+        }
+    }
+}
+
+/// The human-readable (de)serialized representation of a `FloatEvaluator`, as
+/// a map of comparison operators to values.
+#[cfg(feature = "serde")]
+#[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FloatCriterion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    eq: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ne: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gte: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lt: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lte: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    epsilon: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ulps: Option<i64>,
+}
+
+#[cfg(feature = "serde")]
+impl From<FloatEvaluator> for FloatCriterion {
+    fn from(evaluator: FloatEvaluator) -> Self {
+        let mut criterion = Self::default();
+
+        let mut lower = |bound| match bound {
+            FloatRangeBound::Exclusive(x) => criterion.gt = Some(x),
+            FloatRangeBound::Inclusive(x) => criterion.gte = Some(x),
+        };
+
+        match evaluator {
+            FloatEvaluator::EqualTo(x) => {
+                return Self {
+                    eq: Some(x),
+                    ..Self::default()
+                }
+            },
+            FloatEvaluator::NotEqualTo(x) => {
+                return Self {
+                    ne: Some(x),
+                    ..Self::default()
+                }
+            },
+            FloatEvaluator::EqualWithin(x, tolerance) => {
+                return Self {
+                    eq: Some(x),
+                    ..Self::tolerance(tolerance)
+                }
+            },
+            FloatEvaluator::NotEqualWithin(x, tolerance) => {
+                return Self {
+                    ne: Some(x),
+                    ..Self::tolerance(tolerance)
+                }
+            },
+            FloatEvaluator::GreaterThan(bound) | FloatEvaluator::InRange(bound, _) => lower(bound),
+            FloatEvaluator::LessThan(_) => {},
+        }
+
+        match evaluator {
+            FloatEvaluator::LessThan(FloatRangeBound::Exclusive(x))
+            | FloatEvaluator::InRange(_, FloatRangeBound::Exclusive(x)) => criterion.lt = Some(x),
+            FloatEvaluator::LessThan(FloatRangeBound::Inclusive(x))
+            | FloatEvaluator::InRange(_, FloatRangeBound::Inclusive(x)) => criterion.lte = Some(x),
+            _ => {},
+        }
+
+        criterion
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FloatCriterion {
+    fn tolerance(tolerance: FloatTolerance) -> Self {
+        match tolerance {
+            FloatTolerance::Absolute(epsilon) => Self {
+                epsilon: Some(epsilon),
+                ..Self::default()
+            },
+            FloatTolerance::Ulps(ulps) => Self {
+                ulps: Some(ulps),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FloatCriterion> for FloatEvaluator {
+    type Error = &'static str;
+
+    fn try_from(criterion: FloatCriterion) -> Result<Self, Self::Error> {
+        let lower = match (criterion.gt, criterion.gte) {
+            (Some(x), None) => Some(FloatRangeBound::Exclusive(x)),
+            (None, Some(x)) => Some(FloatRangeBound::Inclusive(x)),
+            (None, None) => None,
+            _ => return Err("`gt` and `gte` can't be used together"),
+        };
+
+        let upper = match (criterion.lt, criterion.lte) {
+            (Some(x), None) => Some(FloatRangeBound::Exclusive(x)),
+            (None, Some(x)) => Some(FloatRangeBound::Inclusive(x)),
+            (None, None) => None,
+            _ => return Err("`lt` and `lte` can't be used together"),
+        };
+
+        let tolerance = match (criterion.epsilon, criterion.ulps) {
+            (Some(epsilon), None) => Some(FloatTolerance::Absolute(epsilon)),
+            (None, Some(ulps)) => Some(FloatTolerance::Ulps(ulps)),
+            (None, None) => None,
+            _ => return Err("`epsilon` and `ulps` can't be used together"),
+        };
+
+        if tolerance.is_some_and(|tolerance| !tolerance.is_valid()) {
+            return Err("`epsilon` and `ulps` can't be negative");
+        }
+
+        match (criterion.eq, criterion.ne, tolerance) {
+            (None, None, Some(_)) => return Err("`epsilon` and `ulps` require `eq` or `ne`"),
+            (Some(x), None, Some(tolerance)) if lower.is_none() && upper.is_none() => {
+                return Ok(Self::EqualWithin(x, tolerance))
+            },
+            (None, Some(x), Some(tolerance)) if lower.is_none() && upper.is_none() => {
+                return Ok(Self::NotEqualWithin(x, tolerance))
+            },
+            _ => {},
+        }
+
+        match (criterion.eq, criterion.ne, lower, upper) {
+            (Some(x), None, None, None) if tolerance.is_none() => Ok(Self::EqualTo(x)),
+            (None, Some(x), None, None) if tolerance.is_none() => Ok(Self::NotEqualTo(x)),
+            (None, None, Some(lower), Some(upper)) => Ok(Self::InRange(lower, upper)),
+            (None, None, Some(lower), None) => Ok(Self::GreaterThan(lower)),
+            (None, None, None, Some(upper)) => Ok(Self::LessThan(upper)),
+            (None, None, None, None) => {
+                Err("expected one of `eq`, `ne`, `gt`, `gte`, `lt` or `lte`")
+            },
+            _ => Err("`eq` and `ne` can't be combined with any other comparison"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Evaluator, FloatEvaluator, FloatRangeBound};
+    use float_cmp::Ulps;
+
+    use super::{Evaluator, FloatEvaluator, FloatInterval, FloatRangeBound, FloatTolerance};
 
     #[test]
 
@@ -129,6 +887,65 @@ mod tests {
         assert!(evaluator.evaluate(1.005 + 1.5 + 2.5));
     }
 
+    #[test]
+    fn equal_within() {
+        let evaluator = FloatEvaluator::EqualWithin(5., FloatTolerance::Absolute(0.1));
+        assert!(evaluator.evaluate(5.));
+        assert!(evaluator.evaluate(5.05));
+        assert!(evaluator.evaluate(4.95));
+        assert!(!evaluator.evaluate(5.2));
+
+        let evaluator = FloatEvaluator::EqualWithin(5., FloatTolerance::Ulps(0));
+        assert!(evaluator.evaluate(5.));
+        assert!(!evaluator.evaluate(5. + f64::EPSILON * 4.));
+
+        let evaluator = FloatEvaluator::EqualWithin(f64::INFINITY, FloatTolerance::Ulps(0));
+        assert!(evaluator.evaluate(f64::INFINITY));
+
+        let evaluator = FloatEvaluator::NotEqualWithin(5., FloatTolerance::Absolute(0.1));
+        assert!(!evaluator.evaluate(5.05));
+        assert!(evaluator.evaluate(5.2));
+
+        // Invalid tolerances only consider identical values equal
+        for tolerance in [
+            FloatTolerance::Absolute(-0.1),
+            FloatTolerance::Absolute(f64::NAN),
+            FloatTolerance::Ulps(-4),
+        ] {
+            assert!(!tolerance.is_valid());
+
+            let evaluator = FloatEvaluator::EqualWithin(5., tolerance);
+            assert!(evaluator.evaluate(5.));
+            assert!(!evaluator.evaluate(5. + f64::EPSILON * 4.));
+            assert_eq!(evaluator.interval(), Some(FloatInterval::point(5.)));
+        }
+
+        assert!(FloatTolerance::Absolute(0.).is_valid());
+        assert!(FloatTolerance::Absolute(f64::INFINITY).is_valid());
+    }
+
+    #[test]
+    fn nan() {
+        let evaluators = [
+            FloatEvaluator::EqualTo(5.),
+            FloatEvaluator::EqualWithin(5., FloatTolerance::Absolute(f64::INFINITY)),
+            FloatEvaluator::lt(f64::INFINITY),
+            FloatEvaluator::gte(f64::NEG_INFINITY),
+            FloatEvaluator::range(f64::NEG_INFINITY, f64::INFINITY),
+            FloatEvaluator::EqualTo(f64::NAN),
+        ];
+
+        for evaluator in evaluators {
+            assert!(!evaluator.evaluate(f64::NAN), "{:?}", evaluator);
+        }
+
+        assert!(FloatEvaluator::NotEqualTo(5.).evaluate(f64::NAN));
+        assert!(FloatEvaluator::NotEqualWithin(5., FloatTolerance::Ulps(4)).evaluate(f64::NAN));
+        assert!(!FloatEvaluator::EqualWithin(f64::NAN, FloatTolerance::Ulps(4)).evaluate(f64::NAN));
+        assert!(FloatEvaluator::NotEqualTo(f64::NAN).evaluate(f64::NAN));
+        assert!(FloatEvaluator::NotEqualTo(f64::NAN).evaluate(5.));
+    }
+
     #[test]
     fn less_than_exclusive() {
         let evaluator = FloatEvaluator::LessThan(FloatRangeBound::Exclusive(5.));
@@ -212,4 +1029,225 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serialization() {
+        let cases = [
+            (FloatEvaluator::EqualTo(5.), r#"{"eq":5.0}"#),
+            (FloatEvaluator::NotEqualTo(5.), r#"{"ne":5.0}"#),
+            (
+                FloatEvaluator::EqualWithin(5., FloatTolerance::Absolute(0.5)),
+                r#"{"eq":5.0,"epsilon":0.5}"#,
+            ),
+            (
+                FloatEvaluator::NotEqualWithin(5., FloatTolerance::Ulps(4)),
+                r#"{"ne":5.0,"ulps":4}"#,
+            ),
+            (FloatEvaluator::lt(5.), r#"{"lt":5.0}"#),
+            (FloatEvaluator::lte(5.), r#"{"lte":5.0}"#),
+            (FloatEvaluator::gt(5.), r#"{"gt":5.0}"#),
+            (FloatEvaluator::gte(5.), r#"{"gte":5.0}"#),
+            (FloatEvaluator::range(5., 25.), r#"{"gte":5.0,"lt":25.0}"#),
+            (
+                FloatEvaluator::InRange(
+                    FloatRangeBound::Exclusive(5.),
+                    FloatRangeBound::Inclusive(25.),
+                ),
+                r#"{"gt":5.0,"lte":25.0}"#,
+            ),
+        ];
+
+        for (evaluator, json) in cases {
+            assert_eq!(serde_json::to_string(&evaluator).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<FloatEvaluator>(json).unwrap(),
+                evaluator
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialization_errors() {
+        for json in [
+            r#"{}"#,
+            r#"{"eq":5,"lt":6}"#,
+            r#"{"gt":5,"gte":5}"#,
+            r#"{"lt":5,"lte":5}"#,
+            r#"{"between":5}"#,
+            r#"{"eq":5,"epsilon":0.1,"ulps":4}"#,
+            r#"{"gt":5,"epsilon":0.1}"#,
+            r#"{"eq":5,"lt":6,"ulps":4}"#,
+            r#"{"eq":5,"epsilon":-0.1}"#,
+            r#"{"ne":5,"ulps":-1}"#,
+        ] {
+            assert!(serde_json::from_str::<FloatEvaluator>(json).is_err());
+        }
+
+        // Integers are accepted for convenience
+        assert_eq!(
+            serde_json::from_str::<FloatEvaluator>(r#"{"gte":5,"lt":25}"#).unwrap(),
+            FloatEvaluator::range(5., 25.)
+        );
+    }
+
+    #[test]
+    fn equality_intervals() {
+        // Steps from `x` to an adjacent value `ulps` times
+        let ulps = |x: f64, ulps: i64| {
+            (0..ulps.abs()).fold(x, |x, _| if ulps < 0 { x.prev() } else { x.next() })
+        };
+
+        // Equality is represented by the values within its tolerance, rather
+        // than a single value
+        let interval = FloatEvaluator::EqualTo(5.).interval().unwrap();
+        assert_eq!(
+            interval,
+            FloatInterval::new(
+                Some(FloatRangeBound::Inclusive(ulps(5., -4))),
+                Some(FloatRangeBound::Inclusive(ulps(5., 4)))
+            )
+        );
+        assert!(interval.contains(ulps(5., -1)));
+
+        let values = [
+            0.,
+            -0.,
+            1e-300,
+            -1e-17,
+            5.,
+            -5.,
+            0.1 + 0.2,
+            1e300,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+
+        let tolerances = [
+            FloatTolerance::Absolute(0.5),
+            FloatTolerance::Absolute(0.1),
+            FloatTolerance::Absolute(1e-12),
+            FloatTolerance::Absolute(0.),
+            FloatTolerance::Ulps(0),
+            FloatTolerance::Ulps(4),
+            FloatTolerance::Ulps(1 << 40),
+        ];
+
+        let evaluators = values.iter().flat_map(|&x| {
+            tolerances
+                .iter()
+                .flat_map(move |&tolerance| {
+                    [
+                        FloatEvaluator::EqualWithin(x, tolerance),
+                        FloatEvaluator::NotEqualWithin(x, tolerance),
+                    ]
+                })
+                .chain([FloatEvaluator::EqualTo(x), FloatEvaluator::NotEqualTo(x)])
+        });
+
+        // Every value either side of the intervals' bounds is accepted by the
+        // evaluator if (and only if) it's in one of the intervals
+        for evaluator in evaluators {
+            let intervals = evaluator.intervals();
+
+            for interval in intervals {
+                let bounds = [interval.lower(), interval.upper()];
+
+                for bound in bounds.into_iter().flatten() {
+                    for offset in -2..=2 {
+                        let value = ulps(bound.value(), offset);
+
+                        if value.is_nan() {
+                            continue;
+                        }
+
+                        assert_eq!(
+                            intervals.iter().any(|interval| interval.contains(value)),
+                            evaluator.evaluate(value),
+                            "{:?} for {:?}",
+                            evaluator,
+                            value
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn intervals() {
+        let interval = |evaluator: FloatEvaluator| evaluator.interval().unwrap();
+
+        assert_eq!(FloatEvaluator::NotEqualTo(5.).interval(), None);
+        assert_eq!(
+            interval(FloatEvaluator::EqualWithin(
+                5.,
+                FloatTolerance::Absolute(0.5)
+            )),
+            interval(FloatEvaluator::InRange(
+                FloatRangeBound::Inclusive(4.5),
+                FloatRangeBound::Inclusive(5.5)
+            ))
+        );
+        assert_eq!(
+            interval(FloatEvaluator::EqualWithin(5., FloatTolerance::Ulps(0))),
+            FloatInterval::point(5.)
+        );
+        assert_eq!(
+            interval(FloatEvaluator::EqualWithin(
+                5.,
+                FloatTolerance::Absolute(f64::INFINITY)
+            )),
+            FloatInterval::ALL
+        );
+        assert!(interval(FloatEvaluator::EqualTo(f64::NAN)).is_empty());
+        assert_eq!(
+            interval(FloatEvaluator::gte(f64::NEG_INFINITY)),
+            FloatInterval::ALL
+        );
+
+        // Empty intervals are normalized
+        assert_eq!(
+            interval(FloatEvaluator::range(5., 5.)),
+            FloatInterval::EMPTY
+        );
+        assert!(interval(FloatEvaluator::range(10., 5.)).is_empty());
+        assert!(interval(FloatEvaluator::lt(f64::NAN)).is_empty());
+        assert!(interval(FloatEvaluator::gt(f64::INFINITY)).is_empty());
+        assert!(!interval(FloatEvaluator::lte(5.)).is_empty());
+
+        let low = interval(FloatEvaluator::lt(5.));
+        let high = interval(FloatEvaluator::gte(5.));
+        let mid = interval(FloatEvaluator::range(0., 10.));
+
+        assert!(low.contains(4.9));
+        assert!(!low.contains(5.));
+        assert!(!FloatInterval::ALL.contains(f64::NAN));
+
+        assert!(!low.overlaps(&high));
+        assert!(low.overlaps(&mid) && high.overlaps(&mid));
+        assert_eq!(
+            low.intersection(&mid),
+            interval(FloatEvaluator::range(0., 5.))
+        );
+        assert_eq!(low.intersection(&high), FloatInterval::EMPTY);
+
+        assert_eq!(low.union(&high), Some(FloatInterval::ALL));
+        assert_eq!(
+            low.union(&interval(FloatEvaluator::gt(5.))),
+            None,
+            "5 isn't in either interval"
+        );
+        assert_eq!(mid.union(&high), Some(interval(FloatEvaluator::gte(0.))));
+        assert_eq!(FloatInterval::EMPTY.union(&mid), Some(mid));
+
+        assert!(mid.is_subset(&FloatInterval::ALL));
+        assert!(interval(FloatEvaluator::range(1., 2.)).is_subset(&mid));
+        assert!(!mid.is_subset(&low));
+        assert!(FloatInterval::EMPTY.is_subset(&low));
+        assert!(interval(FloatEvaluator::gt(5.)).is_subset(&high));
+        assert!(!high.is_subset(&interval(FloatEvaluator::gt(5.))));
+    }
 }
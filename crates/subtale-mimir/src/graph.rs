@@ -0,0 +1,389 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Write};
+
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{evaluator::Evaluator, hash::FactHasher, lint::Analyze, ruleset::Ruleset};
+
+/// A rule in a `RuleGraph`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RuleNode<'a, FactKey> {
+    /// The rule's name (if it has one).
+    pub name: Option<&'a str>,
+    /// The rule's position in the collection the ruleset was created from
+    /// (see `Ruleset::position`), which identifies unnamed rules.
+    pub position: usize,
+    /// The rule's specificity (its number of criteria).
+    pub specificity: usize,
+    /// The facts checked by the rule's criteria.
+    pub facts: Vec<&'a FactKey>,
+}
+
+/// A graph of the rules in a ruleset, the facts that they check, and how
+/// they relate to each other, for visualising a ruleset (e.g. during design
+/// reviews).
+///
+/// Rules are referred to by their index in the ruleset (in the order of
+/// `Ruleset::rules`).
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut hurt = Rule::named("hurt", "Ouch!");
+/// hurt.insert("health", FloatEvaluator::lt(50.));
+///
+/// let mut badly_hurt = Rule::named("badly_hurt", "Argh!");
+/// badly_hurt.insert("health", FloatEvaluator::lt(25.));
+///
+/// let ruleset = Ruleset::new(vec![hurt, badly_hurt]);
+/// let graph = RuleGraph::new(&ruleset);
+///
+/// // Both rules match a health of 10, so either could be chosen...
+/// assert_eq!(graph.ties.len(), 1);
+///
+/// // ...and "hurt" matches every query that "badly_hurt" matches
+/// let (rule, other) = graph.subsumes[0];
+/// assert_eq!(graph.rules[rule].name, Some("hurt"));
+/// assert_eq!(graph.rules[other].name, Some("badly_hurt"));
+///
+/// println!("{}", graph.to_dot());
+/// # }
+/// ```
+///
+/// When the `serde` feature is enabled, graphs can be serialized (e.g. as
+/// JSON), with a list of `rules`, and the `ties` and `subsumes` relations as
+/// pairs of rule indices.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RuleGraph<'a, FactKey> {
+    /// The rules in the ruleset (in the order of `Ruleset::rules`).
+    pub rules: Vec<RuleNode<'a, FactKey>>,
+    /// Pairs of equally specific rules that could both match the same query
+    /// (so either could be chosen), with the lower index first.
+    pub ties: Vec<(usize, usize)>,
+    /// Pairs of rules where the first rule matches every query that the second
+    /// rule matches (rules with equivalent criteria subsume each other).
+    pub subsumes: Vec<(usize, usize)>,
+}
+
+impl<'a, FactKey> RuleGraph<'a, FactKey> {
+    /// Builds a graph of the provided ruleset.
+    ///
+    /// Rules whose criteria can never be satisfied don't tie with or subsume
+    /// other rules. Computes in `O(n^2 * m)` time (where `n` is the number of
+    /// rules and `m` is the largest number of evaluators in a rule), so it's
+    /// best suited to tooling rather than running inside your game.
+    pub fn new<FactType, FactEvaluator, Outcome>(
+        ruleset: &'a Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    ) -> Self
+    where
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy + Analyze,
+    {
+        let rules = ruleset.rules();
+
+        let satisfiable: Vec<bool> = rules
+            .iter()
+            .map(|rule| rule.evaluators.values().all(Analyze::is_satisfiable))
+            .collect();
+
+        let mut ties = Vec::new();
+        let mut subsumes = Vec::new();
+
+        for (index, rule) in rules.iter().enumerate() {
+            if !satisfiable[index] {
+                continue;
+            }
+
+            for (other_index, other) in rules.iter().enumerate() {
+                if other_index == index || !satisfiable[other_index] {
+                    continue;
+                }
+
+                // Rules are sorted by specificity, so only later rules can
+                // share this rule's tier
+                if other_index > index
+                    && other.evaluators.len() == rule.evaluators.len()
+                    && rule.evaluators.iter().all(|(fact, evaluator)| {
+                        other
+                            .evaluators
+                            .get(fact)
+                            .into_iter()
+                            .all(|other| evaluator.overlaps(other))
+                    })
+                {
+                    ties.push((index, other_index));
+                }
+
+                let subsumed = rule.evaluators.iter().all(|(fact, evaluator)| {
+                    other
+                        .evaluators
+                        .get(fact)
+                        .is_some_and(|other| other.implies(evaluator))
+                });
+
+                if subsumed {
+                    subsumes.push((index, other_index));
+                }
+            }
+        }
+
+        Self {
+            rules: rules
+                .iter()
+                .enumerate()
+                .map(|(index, rule)| RuleNode {
+                    name: rule.name.as_deref(),
+                    position: ruleset.position(index),
+                    specificity: rule.evaluators.len(),
+                    facts: rule.evaluators.keys().collect(),
+                })
+                .collect(),
+            ties,
+            subsumes,
+        }
+    }
+}
+
+impl<FactKey: core::hash::Hash + Eq + fmt::Display> RuleGraph<'_, FactKey> {
+    /// Exports the graph in Graphviz's DOT language.
+    ///
+    /// Rules (boxes) are connected to the facts (ellipses) that they check by
+    /// dashed lines, and equally specific rules are ranked together. Red lines
+    /// connect rules that could tie, and arrows point from rules to the rules
+    /// that they subsume.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph ruleset {\n    node [shape=box];\n");
+
+        // Writing to a `String` never fails
+        let _ = self.write_dot(&mut dot);
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String) -> fmt::Result {
+        let mut facts = IndexMap::<&FactKey, usize, FactHasher>::default();
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            let label = match rule.name {
+                Some(name) => String::from(name),
+                None => alloc::format!("#{}", rule.position),
+            };
+
+            writeln!(dot, "    rule{} [label={}];", index, DotString(&label))?;
+        }
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            for fact in &rule.facts {
+                let count = facts.len();
+                let id = *facts.entry(*fact).or_insert(count);
+
+                if id == count {
+                    writeln!(
+                        dot,
+                        "    fact{} [label={}, shape=ellipse];",
+                        id,
+                        DotString(&alloc::format!("{}", fact))
+                    )?;
+                }
+
+                writeln!(
+                    dot,
+                    "    rule{} -> fact{} [style=dashed, arrowhead=none];",
+                    index, id
+                )?;
+            }
+        }
+
+        // Rules are sorted by specificity, so each tier is a contiguous run
+        let mut start = 0;
+
+        while start < self.rules.len() {
+            let specificity = self.rules[start].specificity;
+            let end = self.rules[start..]
+                .iter()
+                .position(|rule| rule.specificity != specificity)
+                .map_or(self.rules.len(), |length| start + length);
+
+            write!(dot, "    {{ rank=same;")?;
+            for index in start..end {
+                write!(dot, " rule{};", index)?;
+            }
+            writeln!(dot, " }}")?;
+
+            start = end;
+        }
+
+        for (rule, other) in &self.ties {
+            writeln!(
+                dot,
+                "    rule{} -> rule{} [color=red, dir=none, label=\"could tie\"];",
+                rule, other
+            )?;
+        }
+
+        for (rule, other) in &self.subsumes {
+            writeln!(
+                dot,
+                "    rule{} -> rule{} [label=\"subsumes\"];",
+                rule, other
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A quoted string in Graphviz's DOT language.
+struct DotString<'a>(&'a str);
+
+impl fmt::Display for DotString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\"",
+            self.0.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use crate::{
+        prelude::*,
+        testing::{index, named, rule, TestRuleset},
+    };
+
+    fn ruleset() -> TestRuleset {
+        Ruleset::new(vec![
+            rule("Huh?", &[("concept", FloatEvaluator::range(2., 1.))]),
+            named(
+                "hurt",
+                "Ouch!",
+                &[
+                    ("concept", FloatEvaluator::EqualTo(1.)),
+                    ("health", FloatEvaluator::lt(50.)),
+                ],
+            ),
+            named(
+                "armored",
+                "Tink!",
+                &[
+                    ("concept", FloatEvaluator::EqualTo(1.)),
+                    ("armor", FloatEvaluator::gt(10.)),
+                ],
+            ),
+            named(
+                "healthy",
+                "Ha!",
+                &[
+                    ("concept", FloatEvaluator::EqualTo(1.)),
+                    ("health", FloatEvaluator::gte(50.)),
+                ],
+            ),
+            named(
+                "greet",
+                "Hello!",
+                &[("concept", FloatEvaluator::EqualTo(1.))],
+            ),
+        ])
+    }
+
+    fn pair(a: usize, b: usize) -> (usize, usize) { (a.min(b), a.max(b)) }
+
+    #[test]
+    fn rule_graph() {
+        let ruleset = ruleset();
+        let graph = RuleGraph::new(&ruleset);
+
+        let hurt = index(&ruleset, "Ouch!");
+        let armored = index(&ruleset, "Tink!");
+        let healthy = index(&ruleset, "Ha!");
+        let greet = index(&ruleset, "Hello!");
+
+        assert_eq!(graph.rules[hurt].specificity, 2);
+        assert_eq!(graph.rules[hurt].facts, [&"concept", &"health"]);
+
+        // "hurt" and "healthy" can't both match the same query
+        let mut ties = graph.ties.clone();
+        ties.sort_unstable();
+        let mut expected = vec![pair(hurt, armored), pair(armored, healthy)];
+        expected.sort_unstable();
+        assert_eq!(ties, expected);
+
+        // "greet" subsumes the more specific rules, but not the impossible
+        // rule
+        let mut subsumes = graph.subsumes.clone();
+        subsumes.sort_unstable();
+        let mut expected = vec![(greet, hurt), (greet, armored), (greet, healthy)];
+        expected.sort_unstable();
+        assert_eq!(subsumes, expected);
+    }
+
+    #[test]
+    fn rule_graph_tolerance() {
+        let ruleset = Ruleset::new(vec![
+            rule("Five", &[("health", FloatEvaluator::EqualTo(5.))]),
+            rule("Five to six", &[("health", FloatEvaluator::range(5., 6.))]),
+        ]);
+        let graph = RuleGraph::new(&ruleset);
+
+        // Values just below 5 are equal to 5 (within `EqualTo`'s tolerance),
+        // so the range doesn't subsume the equality
+        assert_eq!(graph.ties, [(0, 1)]);
+        assert!(graph.subsumes.is_empty());
+    }
+
+    #[test]
+    fn rule_graph_dot() {
+        let ruleset = ruleset();
+        let graph = RuleGraph::new(&ruleset);
+        let dot = graph.to_dot();
+
+        let hurt = index(&ruleset, "Ouch!");
+        let greet = index(&ruleset, "Hello!");
+        let impossible = index(&ruleset, "Huh?");
+
+        assert!(dot.starts_with("digraph ruleset {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains(&format!("    rule{} [label=\"hurt\"];\n", hurt)));
+        // Unnamed rules are labelled with their position
+        assert!(dot.contains(&format!("    rule{} [label=\"#0\"];\n", impossible)));
+        assert!(dot.contains("[label=\"health\", shape=ellipse];\n"));
+        assert!(dot.contains(&format!(
+            "    rule{} -> rule{} [label=\"subsumes\"];\n",
+            greet, hurt
+        )));
+        assert_eq!(dot.matches("could tie").count(), 2);
+        assert_eq!(dot.matches("rank=same").count(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn rule_graph_serialization() {
+        let ruleset = ruleset();
+        let graph = RuleGraph::new(&ruleset);
+
+        let json = serde_json::to_value(&graph).unwrap();
+        let hurt = index(&ruleset, "Ouch!");
+
+        assert_eq!(
+            json["rules"][hurt],
+            serde_json::json!({
+                "name": "hurt",
+                "position": 1,
+                "specificity": 2,
+                "facts": ["concept", "health"]
+            })
+        );
+        assert_eq!(json["ties"].as_array().unwrap().len(), 2);
+        assert_eq!(json["subsumes"].as_array().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,56 @@
+use core::hash::Hasher;
+
+/// The hasher used by the maps inside Mímir's types (e.g. `Query::facts` and
+/// `Rule::evaluators`).
+///
+/// When the `std` feature is enabled (the default), this is the standard
+/// library's `RandomState` (and therefore the default hasher of `IndexMap`).
+/// Otherwise, a deterministic `FnvHasher` is used instead.
+#[cfg(feature = "std")]
+pub type FactHasher = std::collections::hash_map::RandomState;
+
+/// The hasher used by the maps inside Mímir's types (e.g. `Query::facts` and
+/// `Rule::evaluators`).
+///
+/// When the `std` feature is enabled (the default), this is the standard
+/// library's `RandomState` (and therefore the default hasher of `IndexMap`).
+/// Otherwise, a deterministic `FnvHasher` is used instead.
+#[cfg(not(feature = "std"))]
+pub type FactHasher = core::hash::BuildHasherDefault<FnvHasher>;
+
+/// An implementation of the (64-bit) FNV-1a hash function, used as the hasher
+/// for Mímir's maps when the `std` feature is disabled.
+///
+/// FNV-1a is fast for the short keys typically used to identify facts, but
+/// isn't resistant to collision attacks.
+#[derive(Clone, Copy, Debug)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self { Self(0xCBF2_9CE4_8422_2325) }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+
+    fn finish(&self) -> u64 { self.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+
+    use super::FnvHasher;
+
+    #[test]
+    fn fnv_hasher() {
+        let mut hasher = FnvHasher::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xAF63_DC4C_8601_EC8C);
+    }
+}
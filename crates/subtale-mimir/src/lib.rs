@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(missing_docs)]
 
 //! Mímir is a contextual query engine for video games with dynamic events (e.g.
@@ -24,6 +25,43 @@
 //! look to match a query against the rule in the ruleset with the
 //! most requirements (i.e. more specific). *(If multiple rules are matched with
 //! the same specificity, one is chosen at random.)*
+//!
+//! Mímir supports `no_std` environments (with an allocator) when its default
+//! `std` feature is disabled. In this case, functions that rely on the
+//! thread-local random number generator (e.g. `Ruleset::evaluate`) are
+//! unavailable: use their `evaluate_with_rng` counterparts instead.
+
+extern crate alloc;
+
+/// Module containing Mímir's compact binary format for rulesets (used by
+/// `Ruleset::to_bytes` and `CompiledRuleset::to_bytes`).
+#[cfg(feature = "binary")]
+pub mod binary;
+
+/// Module containing the `CriterionCache` struct, used to evaluate the
+/// criteria shared between rules at most once per query.
+pub mod cache;
+
+/// Module containing the `CompiledRuleset` struct (a read-only representation
+/// of a `Ruleset` that stores all evaluators in contiguous arrays).
+pub mod compiled;
+
+/// Module containing the `Coverage` struct, which records how often each rule
+/// in a ruleset is matched and selected.
+pub mod coverage;
+
+/// Module containing an importer for rules written in CSV (e.g. exported from
+/// a spreadsheet).
+#[cfg(feature = "csv")]
+pub mod csv;
+
+/// Module containing `diff_rulesets`, which compares two versions of a ruleset.
+pub mod diff;
+
+/// Module containing a parser for Mímir's human-readable rule authoring
+/// language (producing a `Ruleset`).
+#[cfg(feature = "dsl")]
+pub mod dsl;
 
 /// Module containing the `Evaluator` trait, used as a predicate function
 /// against fact values inside rules.
@@ -34,6 +72,27 @@ pub mod evaluator;
 #[cfg(feature = "float")]
 pub mod float;
 
+/// Module containing the `RulesetFile` struct (a versioned envelope used to
+/// store rulesets as assets) and the migrations used to load older files.
+#[cfg(feature = "serde")]
+pub mod file;
+
+/// Module containing the hasher used by the maps inside Mímir's types
+/// (depending on whether the `std` feature is enabled).
+pub mod hash;
+
+/// Module containing `RuleGraph` (a graph of the facts used by a ruleset's
+/// rules, and the rules that could tie or subsume each other).
+pub mod graph;
+
+/// Module containing a static analysis pass over rulesets (reporting
+/// impossible evaluators, duplicate rules and shadowed rules).
+pub mod lint;
+
+/// Module containing the `RuleNetwork` struct (an alternative matcher for a
+/// `Ruleset` that indexes criteria by fact key).
+pub mod network;
+
 /// Prelude module acting as a helper for importing Mímir into your
 /// projects/crates.
 pub mod prelude;
@@ -42,10 +101,78 @@ pub mod prelude;
 /// represent facts (key and value) in a video game's world.
 pub mod query;
 
+/// Module containing the `FactKeyRegistry` struct, used to map fact keys to
+/// dense integer ids (alongside the `DenseQuery` and `DenseRule` structs that
+/// are keyed by those ids).
+pub mod registry;
+
 /// Module containing the `Rule` struct and evaluation logic. Rules define
 /// predicates (`Evaluator`) that evaluate against fact values.
 pub mod rule;
 
+/// Module containing the `Scenario` struct (a data-driven test case for a
+/// ruleset) and `run_scenarios`.
+#[cfg(feature = "std")]
+pub mod scenario;
+
+/// Module containing the `FactSchema` struct, used to declare the fact keys
+/// (and value types) known to your game and validate rulesets and queries
+/// against them.
+pub mod schema;
+
 /// Module containing the `Ruleset` struct (representing a collection of `Rule`
 /// instances with some extra performance considerations).
 pub mod ruleset;
+
+/// Module containing helpers shared by Mímir's tests.
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod testing;
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    /// Asserts (at compile time) that Mímir's core types can be shared
+    /// between threads whenever their generic types can.
+    #[allow(dead_code)]
+    fn core_types_are_send_sync<FactKey, FactType, FactEvaluator, Outcome>()
+    where
+        FactKey: core::hash::Hash + Eq + Send + Sync,
+        FactType: Send + Sync,
+        FactEvaluator: Evaluator<FactType> + Send + Sync,
+        Outcome: Send + Sync,
+    {
+        assert_send_sync::<Query<FactKey, FactType>>();
+        assert_send_sync::<Rule<FactKey, FactType, FactEvaluator, Outcome>>();
+        assert_send_sync::<Ruleset<FactKey, FactType, FactEvaluator, Outcome>>();
+        assert_send_sync::<CompiledRuleset<FactKey, FactType, FactEvaluator, Outcome>>();
+        assert_send_sync::<RuleNetwork<FactKey, FactType, FactEvaluator, Outcome>>();
+    }
+
+    #[test]
+    #[cfg(feature = "float")]
+    fn float_types_are_send_sync() {
+        assert_send_sync::<FloatEvaluator>();
+        assert_send_sync::<Ruleset<String, f64, FloatEvaluator, String>>();
+    }
+
+    #[test]
+    fn rules_do_not_require_send_sync_fact_type() {
+        #[derive(Clone, Copy)]
+        struct NotSync(#[allow(dead_code)] *const u8);
+
+        #[derive(Clone, Copy)]
+        struct AlwaysTrue;
+
+        impl Evaluator<NotSync> for AlwaysTrue {
+            fn evaluate(self, _: NotSync) -> bool { true }
+        }
+
+        // Rules never store values of their fact type
+        assert_send_sync::<Rule<&str, NotSync, AlwaysTrue, ()>>();
+        assert_send_sync::<Ruleset<&str, NotSync, AlwaysTrue, ()>>();
+    }
+}
@@ -0,0 +1,628 @@
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use indexmap::IndexMap;
+
+use crate::{evaluator::Evaluator, hash::FactHasher, ruleset::Ruleset};
+
+/// A trait for evaluators that can be statically analysed (e.g. to prove that
+/// an evaluator can never be satisfied), used by `lint_ruleset`.
+pub trait Analyze: Sized {
+    /// Returns `true` if at least one value satisfies the evaluator.
+    fn is_satisfiable(&self) -> bool;
+
+    /// Returns `true` if every value that satisfies this evaluator also
+    /// satisfies `other`.
+    fn implies(&self, other: &Self) -> bool;
+
+    /// Returns `true` if every possible value satisfies at least one of the
+    /// provided evaluators.
+    fn covers(evaluators: &[Self]) -> bool;
+
+    /// Returns `true` if at least one value satisfies both this evaluator and
+    /// `other`, used by `RuleGraph` to find rules that could tie.
+    ///
+    /// Defaults to `true` if both evaluators are satisfiable, which may report
+    /// an overlap between evaluators that don't actually overlap.
+    fn overlaps(&self, other: &Self) -> bool { self.is_satisfiable() && other.is_satisfiable() }
+}
+
+/// A problem found in a ruleset by `lint_ruleset`.
+///
+/// Rules are referred to by their position in the collection the ruleset was
+/// created from (see `Ruleset::position`), e.g. the order of the rules in a
+/// file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lint<'a, FactKey> {
+    /// The rule has no evaluators, so it matches every query (and is only
+    /// chosen when no more specific rule matches).
+    EmptyRule {
+        /// The position of the empty rule.
+        rule: usize,
+    },
+    /// The evaluator for a fact can never be satisfied (e.g. a range whose
+    /// lower bound is greater than its upper bound), so the rule can never
+    /// match.
+    ImpossibleEvaluator {
+        /// The position of the rule containing the evaluator.
+        rule: usize,
+        /// The fact checked by the evaluator.
+        fact: &'a FactKey,
+    },
+    /// The rule has identical criteria and an identical outcome to an earlier
+    /// rule in the ruleset.
+    Duplicate {
+        /// The position of the duplicate rule.
+        rule: usize,
+        /// The position of the earlier rule that it duplicates.
+        of: usize,
+    },
+    /// Whenever the rule matches a query that contains every fact checked by
+    /// the more specific rules in `by`, at least one of those rules matches it
+    /// too, so the rule is never chosen for complete snapshots of your game's
+    /// world state (it's still chosen for queries that omit those facts).
+    ///
+    /// Only reported when `LintOptions::complete_snapshots` is enabled.
+    ShadowedInCompleteSnapshots {
+        /// The position of the shadowed rule.
+        rule: usize,
+        /// The positions of the more specific rules that shadow it.
+        by: Vec<usize>,
+    },
+}
+
+impl<FactKey> Lint<'_, FactKey> {
+    /// Returns the position of the rule that the lint refers to.
+    pub fn rule(&self) -> usize {
+        match self {
+            Self::EmptyRule { rule }
+            | Self::ImpossibleEvaluator { rule, .. }
+            | Self::Duplicate { rule, .. }
+            | Self::ShadowedInCompleteSnapshots { rule, .. } => *rule,
+        }
+    }
+}
+
+impl<FactKey: fmt::Debug> fmt::Display for Lint<'_, FactKey> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyRule { rule } => write!(f, "rule #{}: the rule has no evaluators", rule),
+            Self::ImpossibleEvaluator { rule, fact } => write!(
+                f,
+                "rule #{}: the evaluator for fact {:?} can never be satisfied",
+                rule, fact
+            ),
+            Self::Duplicate { rule, of } => write!(f, "rule #{}: duplicate of rule #{}", rule, of),
+            Self::ShadowedInCompleteSnapshots { rule, by } => {
+                write!(f, "rule #{}: shadowed by ", rule)?;
+
+                for (index, by) in by.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "rule #{}", by)?;
+                }
+
+                write!(f, " in queries that contain every fact they check")
+            },
+        }
+    }
+}
+
+/// Options for `lint_ruleset_with`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LintOptions {
+    /// Assume that queries are complete snapshots of your game's world state
+    /// (containing every fact checked by the ruleset), and report rules that
+    /// are never chosen as a result (`Lint::ShadowedInCompleteSnapshots`).
+    ///
+    /// Disabled by default, as queries usually omit facts (e.g. an NPC's armor
+    /// when it isn't in combat), and a less specific rule is then chosen
+    /// instead of the rules that check the missing facts.
+    pub complete_snapshots: bool,
+}
+
+/// Analyses the provided ruleset, returning every empty rule, impossible
+/// evaluator and duplicate rule that it contains (see `lint_ruleset_with` to
+/// enable more lints).
+///
+/// Lints are returned in the order of the ruleset's rules. Computes in
+/// `O(n^2 * m)` time (where `n` is the number of rules and `m` is the largest
+/// number of evaluators in a rule), so it's best suited to tooling rather
+/// than running inside your game.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::new("You killed some enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::range(10., 5.));
+///
+/// let ruleset = Ruleset::new(vec![rule]);
+///
+/// assert_eq!(
+///     lint_ruleset(&ruleset),
+///     [Lint::ImpossibleEvaluator {
+///         rule: 0,
+///         fact: &"enemies_killed"
+///     }]
+/// );
+/// # }
+/// ```
+pub fn lint_ruleset<FactKey, FactType, FactEvaluator, Outcome>(
+    ruleset: &Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+) -> Vec<Lint<'_, FactKey>>
+where
+    FactKey: core::hash::Hash + Eq,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy + PartialEq + Analyze,
+    Outcome: PartialEq,
+{
+    lint_ruleset_with(ruleset, LintOptions::default())
+}
+
+/// Analyses the provided ruleset with the provided options (see
+/// `lint_ruleset`).
+///
+/// With `LintOptions::complete_snapshots` enabled, a rule is reported as
+/// shadowed when a more specific rule (or a group of more specific rules that
+/// only differ in the value of a single fact, e.g. `armor < 10` and
+/// `armor >= 10`) matches every query that it matches and that contains the
+/// facts checked by the more specific rules.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut hurt = Rule::new("Ouch!");
+/// hurt.insert("health", FloatEvaluator::lt(25.));
+///
+/// let mut unarmored = Rule::new("That really hurt!");
+/// unarmored.insert("health", FloatEvaluator::lt(50.));
+/// unarmored.insert("armor", FloatEvaluator::lt(10.));
+///
+/// let mut armored = Rule::new("Just a scratch.");
+/// armored.insert("health", FloatEvaluator::lt(50.));
+/// armored.insert("armor", FloatEvaluator::gte(10.));
+///
+/// let ruleset = Ruleset::new(vec![hurt, unarmored, armored]);
+///
+/// // "Ouch!" is still chosen for queries without an armor fact...
+/// assert!(lint_ruleset(&ruleset).is_empty());
+///
+/// // ...but never for queries that contain every fact
+/// let options = LintOptions {
+///     complete_snapshots: true,
+/// };
+///
+/// assert_eq!(
+///     lint_ruleset_with(&ruleset, options),
+///     [Lint::ShadowedInCompleteSnapshots {
+///         rule: 0,
+///         by: vec![1, 2]
+///     }]
+/// );
+/// # }
+/// ```
+pub fn lint_ruleset_with<FactKey, FactType, FactEvaluator, Outcome>(
+    ruleset: &Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    options: LintOptions,
+) -> Vec<Lint<'_, FactKey>>
+where
+    FactKey: core::hash::Hash + Eq,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy + PartialEq + Analyze,
+    Outcome: PartialEq,
+{
+    let rules = ruleset.rules();
+    let position = |index| ruleset.position(index);
+    let mut lints = Vec::new();
+
+    let satisfiable: Vec<bool> = rules
+        .iter()
+        .map(|rule| rule.evaluators.values().all(Analyze::is_satisfiable))
+        .collect();
+
+    for (index, rule) in rules.iter().enumerate() {
+        if rule.evaluators.is_empty() {
+            lints.push(Lint::EmptyRule {
+                rule: position(index),
+            });
+        }
+
+        for (fact, evaluator) in &rule.evaluators {
+            if !evaluator.is_satisfiable() {
+                lints.push(Lint::ImpossibleEvaluator {
+                    rule: position(index),
+                    fact,
+                });
+            }
+        }
+
+        // Rules are sorted by specificity, so duplicates are always adjacent
+        // to rules with the same number of evaluators
+        let duplicate = rules[..index]
+            .iter()
+            .enumerate()
+            .rev()
+            .take_while(|(_, other)| other.evaluators.len() == rule.evaluators.len())
+            .find(|(_, other)| {
+                other.evaluators == rule.evaluators && other.outcome == rule.outcome
+            });
+
+        if let Some((of, _)) = duplicate {
+            lints.push(Lint::Duplicate {
+                rule: position(index),
+                of: position(of),
+            });
+        }
+
+        if !options.complete_snapshots || !satisfiable[index] {
+            continue;
+        }
+
+        // More specific rules that match whenever this rule matches, grouped
+        // by the single fact that they additionally check (if any)
+        let mut groups = IndexMap::<&FactKey, Vec<(usize, FactEvaluator)>, FactHasher>::default();
+        let mut shadowed_by = None;
+
+        for (other_index, other) in rules.iter().enumerate() {
+            if other.evaluators.len() <= rule.evaluators.len() {
+                break;
+            }
+
+            if !satisfiable[other_index] {
+                continue;
+            }
+
+            let mut extra = None;
+            let mut implied = true;
+
+            for (fact, evaluator) in &other.evaluators {
+                match rule.evaluators.get(fact) {
+                    Some(own) if own.implies(evaluator) => {},
+                    Some(_) => implied = false,
+                    // Extra criteria that accept every value don't matter
+                    None if FactEvaluator::covers(&[*evaluator]) => {},
+                    None if extra.is_none() => extra = Some((fact, *evaluator)),
+                    None => implied = false,
+                }
+
+                if !implied {
+                    break;
+                }
+            }
+
+            if !implied {
+                continue;
+            }
+
+            match extra {
+                None => {
+                    shadowed_by = Some(vec![other_index]);
+                    break;
+                },
+                Some((fact, evaluator)) => groups
+                    .entry(fact)
+                    .or_default()
+                    .push((other_index, evaluator)),
+            }
+        }
+
+        let shadowed_by = shadowed_by.or_else(|| {
+            groups.into_values().find_map(|group| {
+                let evaluators: Vec<_> = group.iter().map(|(_, evaluator)| *evaluator).collect();
+                FactEvaluator::covers(&evaluators).then(|| {
+                    group
+                        .into_iter()
+                        .map(|(index, _)| index)
+                        .collect::<Vec<_>>()
+                })
+            })
+        });
+
+        if let Some(by) = shadowed_by {
+            lints.push(Lint::ShadowedInCompleteSnapshots {
+                rule: position(index),
+                by: by.into_iter().map(position).collect(),
+            });
+        }
+    }
+
+    lints
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use crate::{prelude::*, testing::rule};
+
+    const COMPLETE_SNAPSHOTS: LintOptions = LintOptions {
+        complete_snapshots: true,
+    };
+
+    #[test]
+    fn impossible_evaluators() {
+        let ruleset = Ruleset::new(vec![
+            rule("reversed", &[("health", FloatEvaluator::range(25., 5.))]),
+            rule(
+                "empty",
+                &[(
+                    "health",
+                    FloatEvaluator::InRange(
+                        FloatRangeBound::Exclusive(5.),
+                        FloatRangeBound::Inclusive(5.),
+                    ),
+                )],
+            ),
+            rule(
+                "point",
+                &[(
+                    "health",
+                    FloatEvaluator::InRange(
+                        FloatRangeBound::Inclusive(5.),
+                        FloatRangeBound::Inclusive(5.),
+                    ),
+                )],
+            ),
+        ]);
+
+        let lints = lint_ruleset(&ruleset);
+
+        assert_eq!(
+            lints,
+            [
+                Lint::ImpossibleEvaluator {
+                    rule: 0,
+                    fact: &"health"
+                },
+                Lint::ImpossibleEvaluator {
+                    rule: 1,
+                    fact: &"health"
+                },
+            ]
+        );
+        assert_eq!(
+            lints[0].to_string(),
+            r#"rule #0: the evaluator for fact "health" can never be satisfied"#
+        );
+    }
+
+    #[test]
+    fn empty_rules() {
+        let ruleset = Ruleset::new(vec![
+            rule("Hello!", &[("health", FloatEvaluator::gt(50.))]),
+            rule("Hmm.", &[]),
+        ]);
+
+        let lints = lint_ruleset(&ruleset);
+
+        assert_eq!(lints, [Lint::EmptyRule { rule: 1 }]);
+        assert_eq!(lints[0].to_string(), "rule #1: the rule has no evaluators");
+    }
+
+    #[test]
+    fn duplicate_rules() {
+        let ruleset = Ruleset::new(vec![
+            rule(
+                "Ouch!",
+                &[
+                    ("health", FloatEvaluator::lt(25.)),
+                    ("enemies", FloatEvaluator::gt(0.)),
+                ],
+            ),
+            rule("Hello!", &[("health", FloatEvaluator::gt(50.))]),
+            // Criteria in a different order are still identical
+            rule(
+                "Ouch!",
+                &[
+                    ("enemies", FloatEvaluator::gt(0.)),
+                    ("health", FloatEvaluator::lt(25.)),
+                ],
+            ),
+            // A different outcome isn't a duplicate
+            rule(
+                "Help!",
+                &[
+                    ("health", FloatEvaluator::lt(25.)),
+                    ("enemies", FloatEvaluator::gt(0.)),
+                ],
+            ),
+        ]);
+
+        // Rules are referred to by their positions, rather than their indices
+        // in the sorted ruleset
+        let lints = lint_ruleset(&ruleset);
+
+        assert_eq!(lints, [Lint::Duplicate { rule: 2, of: 0 }]);
+        assert_eq!(lints[0].to_string(), "rule #2: duplicate of rule #0");
+    }
+
+    #[test]
+    fn shadowed_rules() {
+        let ruleset = Ruleset::new(vec![
+            rule("hurt", &[("health", FloatEvaluator::lt(25.))]),
+            rule(
+                "hurt and unarmored",
+                &[
+                    ("health", FloatEvaluator::lt(50.)),
+                    ("armor", FloatEvaluator::lt(10.)),
+                ],
+            ),
+            rule(
+                "hurt and armored",
+                &[
+                    ("health", FloatEvaluator::lt(50.)),
+                    ("armor", FloatEvaluator::gte(10.)),
+                ],
+            ),
+            rule("healthy", &[("health", FloatEvaluator::gte(50.))]),
+        ]);
+
+        // "hurt" is chosen for queries without an armor fact...
+        let mut query = Query::new();
+        query.insert("health", 10.);
+        assert_eq!(ruleset.evaluate(&query).unwrap().outcome, "hurt");
+        assert!(lint_ruleset(&ruleset).is_empty());
+
+        // ...but always loses to one of the armor rules otherwise, whereas
+        // "healthy" doesn't overlap with them
+        let lints = lint_ruleset_with(&ruleset, COMPLETE_SNAPSHOTS);
+
+        assert_eq!(
+            lints,
+            [Lint::ShadowedInCompleteSnapshots {
+                rule: 0,
+                by: vec![1, 2]
+            }]
+        );
+        assert_eq!(
+            lints[0].to_string(),
+            "rule #0: shadowed by rule #1, rule #2 in queries that contain every fact they check"
+        );
+    }
+
+    #[test]
+    fn shadowed_by_single_rule() {
+        let ruleset = Ruleset::new(vec![
+            rule("low", &[("health", FloatEvaluator::range(0., 10.))]),
+            rule(
+                "low, any armor",
+                &[
+                    ("health", FloatEvaluator::lt(25.)),
+                    ("armor", FloatEvaluator::gte(f64::NEG_INFINITY)),
+                ],
+            ),
+        ]);
+
+        // The armor criterion accepts every value
+        assert_eq!(
+            lint_ruleset_with(&ruleset, COMPLETE_SNAPSHOTS),
+            [Lint::ShadowedInCompleteSnapshots {
+                rule: 0,
+                by: vec![1]
+            }]
+        );
+    }
+
+    #[test]
+    fn not_shadowed() {
+        let ruleset = Ruleset::new(vec![
+            rule("hurt", &[("health", FloatEvaluator::lt(25.))]),
+            // A gap between the armor rules (exactly 10) lets "hurt" match
+            rule(
+                "hurt and unarmored",
+                &[
+                    ("health", FloatEvaluator::lt(50.)),
+                    ("armor", FloatEvaluator::lt(10.)),
+                ],
+            ),
+            rule(
+                "hurt and armored",
+                &[
+                    ("health", FloatEvaluator::lt(50.)),
+                    ("armor", FloatEvaluator::gt(10.)),
+                ],
+            ),
+            // Doesn't cover all of "hurt"
+            rule(
+                "nearly dead",
+                &[
+                    ("health", FloatEvaluator::lt(5.)),
+                    ("armor", FloatEvaluator::EqualTo(10.)),
+                ],
+            ),
+        ]);
+
+        assert!(lint_ruleset_with(&ruleset, COMPLETE_SNAPSHOTS).is_empty());
+    }
+
+    #[test]
+    fn not_shadowed_within_tolerance() {
+        let ruleset = Ruleset::new(vec![
+            rule("five", &[("health", FloatEvaluator::EqualTo(5.))]),
+            rule(
+                "five to six, any armor",
+                &[
+                    ("health", FloatEvaluator::range(5., 6.)),
+                    ("armor", FloatEvaluator::gte(f64::NEG_INFINITY)),
+                ],
+            ),
+        ]);
+
+        // A value just below 5 is equal to 5 (within `EqualTo`'s tolerance),
+        // but outside of the range
+        assert!(lint_ruleset_with(&ruleset, COMPLETE_SNAPSHOTS).is_empty());
+
+        let mut query = Query::new();
+        query.insert("health", 5. - f64::EPSILON * 4.);
+        query.insert("armor", 0.);
+        assert_eq!(ruleset.evaluate(&query).unwrap().outcome, "five");
+    }
+
+    #[test]
+    fn float_evaluator_analysis() {
+        assert!(FloatEvaluator::EqualTo(5.5).implies(&FloatEvaluator::range(5., 6.)));
+        assert!(!FloatEvaluator::EqualTo(5.).implies(&FloatEvaluator::range(5., 6.)));
+        assert!(!FloatEvaluator::EqualTo(5.).implies(&FloatEvaluator::gte(5.)));
+        assert!(FloatEvaluator::EqualTo(5.).overlaps(&FloatEvaluator::lt(5.)));
+        assert!(!FloatEvaluator::EqualTo(6.).implies(&FloatEvaluator::range(5., 6.)));
+        assert!(FloatEvaluator::gt(5.).implies(&FloatEvaluator::gte(5.)));
+        assert!(!FloatEvaluator::gte(5.).implies(&FloatEvaluator::gt(5.)));
+        assert!(FloatEvaluator::lt(4.).implies(&FloatEvaluator::NotEqualTo(5.)));
+        assert!(!FloatEvaluator::NotEqualTo(5.).implies(&FloatEvaluator::lt(5.)));
+
+        let within = |x, epsilon| FloatEvaluator::EqualWithin(x, FloatTolerance::Absolute(epsilon));
+        let not_within =
+            |x, epsilon| FloatEvaluator::NotEqualWithin(x, FloatTolerance::Absolute(epsilon));
+
+        assert!(within(5., 0.5).implies(&FloatEvaluator::range(4., 6.)));
+        assert!(!within(5., 1.5).implies(&FloatEvaluator::range(4., 6.)));
+        assert!(not_within(5., 1.).implies(&FloatEvaluator::NotEqualTo(5.)));
+        assert!(!FloatEvaluator::NotEqualTo(5.).implies(&not_within(5., 1.)));
+        assert!(FloatEvaluator::NotEqualTo(f64::NAN).is_satisfiable());
+        assert!(FloatEvaluator::covers(&[FloatEvaluator::NotEqualTo(
+            f64::NAN
+        )]));
+        assert!(FloatEvaluator::covers(&[
+            not_within(5., 1.),
+            within(5., 1.)
+        ]));
+        assert!(!FloatEvaluator::covers(&[
+            not_within(5., 1.),
+            within(5., 0.5)
+        ]));
+
+        assert!(FloatEvaluator::covers(&[
+            FloatEvaluator::lt(5.),
+            FloatEvaluator::gte(5.)
+        ]));
+        assert!(!FloatEvaluator::covers(&[
+            FloatEvaluator::lt(5.),
+            FloatEvaluator::gt(5.)
+        ]));
+        assert!(FloatEvaluator::covers(&[
+            FloatEvaluator::lt(5.),
+            FloatEvaluator::gt(5.),
+            FloatEvaluator::EqualTo(5.)
+        ]));
+        assert!(FloatEvaluator::covers(&[
+            FloatEvaluator::NotEqualTo(5.),
+            FloatEvaluator::range(0., 10.)
+        ]));
+        assert!(FloatEvaluator::covers(&[
+            FloatEvaluator::lte(0.),
+            FloatEvaluator::range(10., 20.),
+            FloatEvaluator::range(-5., 15.),
+            FloatEvaluator::gt(19.)
+        ]));
+        assert!(!FloatEvaluator::covers(&[FloatEvaluator::range(0., 10.)]));
+
+        assert!(FloatEvaluator::lt(5.).overlaps(&FloatEvaluator::gt(4.)));
+        assert!(!FloatEvaluator::lt(5.).overlaps(&FloatEvaluator::gte(5.)));
+        assert!(FloatEvaluator::lte(5.).overlaps(&FloatEvaluator::gte(5.)));
+        assert!(!FloatEvaluator::EqualTo(5.).overlaps(&FloatEvaluator::NotEqualTo(5.)));
+        assert!(within(5., 1.).overlaps(&FloatEvaluator::NotEqualTo(5.)));
+        assert!(!FloatEvaluator::range(25., 5.).overlaps(&FloatEvaluator::gt(0.)));
+    }
+}
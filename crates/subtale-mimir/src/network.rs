@@ -0,0 +1,319 @@
+use alloc::{vec, vec::Vec};
+use core::hash::Hash;
+
+use indexmap::IndexMap;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    cache,
+    evaluator::Evaluator,
+    hash::FactHasher,
+    query::Query,
+    rule::Rule,
+    ruleset::Ruleset,
+};
+
+/// A node in a `RuleNetwork` representing a unique evaluator for a fact, along
+/// with the rules that contain it.
+struct AlphaNode<FactEvaluator> {
+    evaluator: FactEvaluator,
+    rules: Vec<usize>,
+}
+
+/// A `RuleNetwork` is an alternative matcher for a `Ruleset` that indexes the
+/// ruleset's criteria by fact key (in the spirit of a Rete network's alpha
+/// memory).
+///
+/// Rather than scanning every rule in the ruleset, evaluating a query only
+/// looks at the criteria that refer to facts present in the query. Each unique
+/// criterion is evaluated once, and every rule containing a criterion that
+/// evaluates to true is credited; rules credited for all of their criteria
+/// match the query.
+///
+/// The results are exactly the same as evaluating the underlying `Ruleset`
+/// (including the order of the returned rules), but the cost of evaluation
+/// scales with the number of criteria that refer to facts in the query (and
+/// the rules that contain them) rather than the number of rules in the
+/// ruleset, which makes the network well suited to rulesets with tens of
+/// thousands of rules.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::new("You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+///
+/// let network = RuleNetwork::new(vec![rule]);
+///
+/// let mut query = Query::new();
+/// query.insert("enemies_killed", 5.);
+///
+/// assert_eq!(
+///     network.evaluate(&query).unwrap().outcome,
+///     "You killed 5 enemies!"
+/// );
+/// # }
+/// ```
+pub struct RuleNetwork<FactKey, FactType, FactEvaluator: Evaluator<FactType>, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    ruleset: Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    /// The unique criteria of the ruleset, grouped by fact key.
+    alpha: IndexMap<FactKey, Vec<AlphaNode<FactEvaluator>>, FactHasher>,
+    /// The indices of rules without any evaluators (which always match).
+    unconditional: Vec<usize>,
+}
+
+impl<
+        FactKey: core::hash::Hash + Eq + Clone,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy + Hash + PartialEq,
+        Outcome,
+    > RuleNetwork<FactKey, FactType, FactEvaluator, Outcome>
+{
+    /// Creates a new network (and the underlying ruleset) from the provided
+    /// collection of rules.
+    pub fn new(rules: Vec<Rule<FactKey, FactType, FactEvaluator, Outcome>>) -> Self {
+        Self::from(Ruleset::new(rules))
+    }
+
+    /// Returns the ruleset that the network was built from.
+    pub fn ruleset(&self) -> &Ruleset<FactKey, FactType, FactEvaluator, Outcome> { &self.ruleset }
+
+    /// Evaluates the network against the provided query.
+    ///
+    /// Returns the most specific (most evaluators) rules in the ruleset that
+    /// evaluate to true for the provided query (identical to
+    /// `Ruleset::evaluate_all`).
+    pub fn evaluate_all(
+        &self,
+        query: &Query<FactKey, FactType>,
+    ) -> Vec<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        let rules = self.ruleset.rules();
+
+        // The number of criteria that have evaluated to true for each rule
+        // (only tracking the rules that have been credited, so that the cost
+        // doesn't depend on the size of the ruleset)
+        let mut credits = IndexMap::<usize, usize, FactHasher>::default();
+        let mut matched = Vec::<usize>::new();
+        let mut tier = 0;
+
+        for (fact, value) in query.facts() {
+            let Some(nodes) = self.alpha.get(fact) else {
+                continue;
+            };
+
+            for node in nodes {
+                if !node.evaluator.evaluate(*value) {
+                    continue;
+                }
+
+                for &rule in &node.rules {
+                    let credit = credits.entry(rule).or_default();
+                    *credit += 1;
+
+                    let specificity = rules[rule].evaluators.len();
+
+                    if *credit < specificity || specificity < tier {
+                        continue;
+                    }
+
+                    if specificity > tier {
+                        tier = specificity;
+                        matched.clear();
+                    }
+
+                    matched.push(rule);
+                }
+            }
+        }
+
+        if matched.is_empty() {
+            matched.extend_from_slice(&self.unconditional);
+        }
+
+        // Return the rules in the same order as the ruleset stores them
+        matched.sort_unstable();
+        matched.into_iter().map(|rule| &rules[rule]).collect()
+    }
+
+    /// Evaluates the network against the provided query.
+    ///
+    /// Returns the most specific (most evaluators) rule in the ruleset that
+    /// evaluates to true for the provided query. If multiple rules evaluate
+    /// to true with the same specificness, one is picked at random.
+    #[cfg(feature = "std")]
+    pub fn evaluate(
+        &self,
+        query: &Query<FactKey, FactType>,
+    ) -> Option<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        self.evaluate_with_rng(query, &mut rand::thread_rng())
+    }
+
+    /// Evaluates the network against the provided query, breaking ties between
+    /// equally specific rules with the provided random number generator (see
+    /// `Ruleset::evaluate_with_rng`).
+    ///
+    /// Unlike `RuleNetwork::evaluate`, this is available without the `std`
+    /// feature.
+    pub fn evaluate_with_rng<R: Rng + ?Sized>(
+        &self,
+        query: &Query<FactKey, FactType>,
+        rng: &mut R,
+    ) -> Option<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        let matched = self.evaluate_all(query);
+        matched.choose(rng).copied()
+    }
+}
+
+impl<
+        FactKey: core::hash::Hash + Eq + Clone,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy + Hash + PartialEq,
+        Outcome,
+    > From<Ruleset<FactKey, FactType, FactEvaluator, Outcome>>
+    for RuleNetwork<FactKey, FactType, FactEvaluator, Outcome>
+{
+    fn from(ruleset: Ruleset<FactKey, FactType, FactEvaluator, Outcome>) -> Self {
+        let mut alpha = IndexMap::<FactKey, Vec<AlphaNode<FactEvaluator>>, FactHasher>::default();
+        let mut unconditional = Vec::new();
+
+        let ids = cache::dedup(ruleset.rules().iter().flat_map(|rule| &rule.evaluators));
+        let mut ids = ids.into_iter();
+
+        // The position (in `alpha`) of each unique criterion's node
+        let mut positions = Vec::<(usize, usize)>::new();
+
+        for (index, rule) in ruleset.rules().iter().enumerate() {
+            if rule.evaluators.is_empty() {
+                unconditional.push(index);
+            }
+
+            for ((fact, evaluator), id) in rule.evaluators.iter().zip(&mut ids) {
+                // Criteria are given ids in order of first occurrence, so an
+                // id that hasn't been seen yet is always a new node
+                if id < positions.len() {
+                    let (fact, node) = positions[id];
+                    alpha[fact][node].rules.push(index);
+                    continue;
+                }
+
+                let entry = alpha.entry(fact.clone());
+                let fact = entry.index();
+                let nodes = entry.or_default();
+
+                positions.push((fact, nodes.len()));
+                nodes.push(AlphaNode {
+                    evaluator: *evaluator,
+                    rules: vec![index],
+                });
+            }
+        }
+
+        Self {
+            ruleset,
+            alpha,
+            unconditional,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::prelude::*;
+
+    fn random_evaluator(rng: &mut StdRng) -> FloatEvaluator {
+        let x = rng.gen_range(0..=4) as f64;
+
+        match rng.gen_range(0..6) {
+            0 => FloatEvaluator::EqualTo(x),
+            1 => FloatEvaluator::NotEqualTo(x),
+            2 => FloatEvaluator::lt(x),
+            3 => FloatEvaluator::gte(x),
+            4 => FloatEvaluator::range(x, x + 2.),
+            _ => FloatEvaluator::InRange(
+                FloatRangeBound::Exclusive(x - 1.),
+                FloatRangeBound::Inclusive(x + 1.),
+            ),
+        }
+    }
+
+    #[test]
+    fn network_evaluation() {
+        let mut rule = Rule::new("You killed 5 enemies!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+
+        let mut more_specific_rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+        more_specific_rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened", FloatEvaluator::gt(2.));
+
+        let fallback_rule = Rule::new("Nothing happened.");
+
+        let network = RuleNetwork::new(vec![rule, more_specific_rule, fallback_rule]);
+
+        let mut query = Query::new();
+        query.insert("enemies_killed", 2.5 + 1.5 + 1.);
+
+        assert_eq!(
+            network.evaluate(&query).unwrap().outcome,
+            "You killed 5 enemies!"
+        );
+
+        query.insert("doors_opened", 10.);
+
+        assert_eq!(
+            network.evaluate(&query).unwrap().outcome,
+            "You killed 5 enemies and opened 2 doors!"
+        );
+
+        assert_eq!(
+            network.evaluate(&Query::new()).unwrap().outcome,
+            "Nothing happened."
+        );
+    }
+
+    #[test]
+    fn network_matches_ruleset() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..50 {
+            let rules = (0..rng.gen_range(0..200))
+                .map(|outcome| {
+                    let mut rule = Rule::new(outcome);
+                    for _ in 0..rng.gen_range(0..=4) {
+                        rule.insert(rng.gen_range(0..6), random_evaluator(&mut rng));
+                    }
+                    rule
+                })
+                .collect();
+
+            let network = RuleNetwork::new(rules);
+
+            for _ in 0..50 {
+                let mut query = Query::new();
+                for _ in 0..rng.gen_range(0..=6) {
+                    query.insert(rng.gen_range(0..6), rng.gen_range(-1..=6) as f64);
+                }
+
+                let expected: Vec<_> = network
+                    .ruleset()
+                    .evaluate_all(&query)
+                    .into_iter()
+                    .map(|rule| rule as *const _)
+                    .collect();
+                let actual: Vec<_> = network
+                    .evaluate_all(&query)
+                    .into_iter()
+                    .map(|rule| rule as *const _)
+                    .collect();
+
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+}
@@ -1,3 +1,19 @@
 #[cfg(feature = "float")]
 pub use crate::float::*;
-pub use crate::{evaluator::*, query::*, rule::*, ruleset::*};
+#[cfg(feature = "std")]
+pub use crate::scenario::*;
+pub use crate::{
+    cache::*,
+    compiled::*,
+    coverage::*,
+    diff::*,
+    evaluator::*,
+    graph::*,
+    lint::*,
+    network::*,
+    query::*,
+    registry::*,
+    rule::*,
+    ruleset::*,
+    schema::*,
+};
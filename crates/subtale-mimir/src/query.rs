@@ -1,7 +1,9 @@
-use indexmap::IndexMap;
+use indexmap::{Equivalent, IndexMap};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::hash::FactHasher;
+
 /// A `Query<FactKey, FactType>` represents a collection of facts about your
 /// video game's world, mapped from a fact's unique identifier (`FactKey`) to
 /// current value (`FactType`).
@@ -36,27 +38,50 @@ use serde::{Deserialize, Serialize};
 /// query.insert("player_health", QueryValue::Decimal(12.34));
 /// query.insert("reached_checkpoint", QueryValue::Flag(false));
 /// ```
+///
+/// Facts that only describe momentary events (e.g. the player just died, or
+/// an NPC heard a gunshot) can be inserted with a time-to-live, after which
+/// they're removed from the query by `Query::tick` (or `Query::purge_expired`):
+///
+/// ```
+/// use subtale_mimir::prelude::*;
+///
+/// let mut query: Query<&str, usize> = Query::new();
+/// query.insert_with_ttl("heard_gunshot", 1, 5.);
+///
+/// query.tick(4.);
+/// assert!(query.facts().contains_key("heard_gunshot"));
+///
+/// query.tick(5.);
+/// assert!(!query.facts().contains_key("heard_gunshot"));
+/// ```
 #[derive(Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Query<FactKey, FactType>
 where
-    FactKey: std::hash::Hash + Eq,
+    FactKey: core::hash::Hash + Eq,
 {
     /// The facts currently stored within the query (using an `IndexMap` as the
     /// data structure implementation).
-    pub facts: IndexMap<FactKey, FactType>,
+    facts: IndexMap<FactKey, FactType, FactHasher>,
+    /// The time at which each expiring fact should be removed from the query.
+    #[cfg_attr(feature = "serde", serde(default))]
+    expiries: IndexMap<FactKey, f64, FactHasher>,
+    /// The query's current time (as last supplied to `Query::tick`).
+    #[cfg_attr(feature = "serde", serde(default))]
+    now: f64,
 }
 
-impl<FactKey: std::hash::Hash + Eq, FactType: Copy>
-    Query<FactKey, FactType>
-{
+impl<FactKey: core::hash::Hash + Eq, FactType: Copy> Query<FactKey, FactType> {
     /// Instantiates a new instance of `Query` without allocating an underlying
     /// `IndexMap`.
     ///
     /// Computes in `O(1)` time.
     pub fn new() -> Self {
         Self {
-            facts: IndexMap::new(),
+            facts: IndexMap::default(),
+            expiries: IndexMap::default(),
+            now: 0.,
         }
     }
 
@@ -66,26 +91,128 @@ impl<FactKey: std::hash::Hash + Eq, FactType: Copy>
     /// Computes in `O(n)` time.
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            facts: IndexMap::with_capacity(capacity),
+            facts: IndexMap::with_capacity_and_hasher(capacity, FactHasher::default()),
+            expiries: IndexMap::default(),
+            now: 0.,
         }
     }
 
+    /// Returns the facts currently stored within the query.
+    ///
+    /// Facts are modified through the query (e.g. with `Query::insert` or
+    /// `Query::remove`), so that their time-to-lives are kept in sync.
+    pub fn facts(&self) -> &IndexMap<FactKey, FactType, FactHasher> { &self.facts }
+
     /// Inserts a new fact into the query (at the end of the query's underlying
     /// map).
     ///
     /// If a fact already exists in the query with the same key, the value is
     /// overwritten (but the index position of the fact in the query is
-    /// retained).
+    /// retained). Any time-to-live previously set for the fact is cleared.
     ///
     /// Computes in `O(1)` time (amortized average, depending on current
     /// capacity).
-    pub fn insert(&mut self, fact: FactKey, value: FactType) { self.facts.insert(fact, value); }
+    pub fn insert(&mut self, fact: FactKey, value: FactType) {
+        if !self.expiries.is_empty() {
+            self.expiries.swap_remove(&fact);
+        }
+
+        self.facts.insert(fact, value);
+    }
+
+    /// Inserts a new fact into the query that expires once `ttl` has elapsed
+    /// since the query's current time (see `Query::tick`).
+    ///
+    /// Expired facts remain in the query (and are visible to rules) until the
+    /// next call to `Query::tick` or `Query::purge_expired`.
+    ///
+    /// Computes in `O(1)` time (amortized average, depending on current
+    /// capacity).
+    pub fn insert_with_ttl(&mut self, fact: FactKey, value: FactType, ttl: f64)
+    where
+        FactKey: Clone,
+    {
+        self.expiries.insert(fact.clone(), self.now + ttl);
+        self.facts.insert(fact, value);
+    }
+
+    /// Removes a fact from the query (along with any time-to-live set for it),
+    /// returning its value (or `None` if the fact wasn't in the query).
+    ///
+    /// The order of the remaining facts is retained.
+    ///
+    /// Computes in `O(n)` time.
+    pub fn remove<Q>(&mut self, fact: &Q) -> Option<FactType>
+    where
+        Q: core::hash::Hash + Equivalent<FactKey> + ?Sized,
+    {
+        self.expiries.swap_remove(fact);
+        self.facts.shift_remove(fact)
+    }
+
+    /// Removes every fact from the query (along with their time-to-lives),
+    /// retaining the query's current time.
+    ///
+    /// Computes in `O(n)` time.
+    pub fn clear(&mut self) {
+        self.facts.clear();
+        self.expiries.clear();
+    }
+
+    /// Returns the time at which the provided fact expires (or `None` if the
+    /// fact doesn't have a time-to-live, or isn't in the query).
+    pub fn expiry(&self, fact: &FactKey) -> Option<f64> { self.expiries.get(fact).copied() }
+
+    /// Returns the query's current time (as last supplied to `Query::tick`).
+    pub fn now(&self) -> f64 { self.now }
+
+    /// Advances the query's current time to `now` and removes any facts that
+    /// have expired.
+    ///
+    /// The unit of time is up to your game (e.g. seconds since the level was
+    /// loaded), so long as it's used consistently with `insert_with_ttl`.
+    ///
+    /// Computes in `O(n)` time.
+    pub fn tick(&mut self, now: f64) {
+        self.now = now;
+        self.purge_expired();
+    }
+
+    /// Removes all facts whose expiry is at or before the query's current
+    /// time.
+    ///
+    /// Computes in `O(n)` time.
+    pub fn purge_expired(&mut self) {
+        if self.expiries.is_empty() {
+            return;
+        }
+
+        let now = self.now;
+        let expiries = &self.expiries;
+        self.facts
+            .retain(|fact, _| !expiries.get(fact).is_some_and(|expiry| *expiry <= now));
+        self.expiries.retain(|_, expiry| *expiry > now);
+    }
 
     /// Appends all facts from another query to the query (at the end of the
     /// query's underlying map).
     ///
-    /// Computes in `O(1)` time.
-    pub fn extend(&mut self, query: Query<FactKey, FactType>) { self.facts.extend(query.facts); }
+    /// Time-to-lives set on facts in the other query are retained, whereas
+    /// facts that already existed in this query are overwritten (clearing any
+    /// time-to-live, as with `Query::insert`).
+    ///
+    /// Computes in `O(n)` time (where `n` is the number of facts in the other
+    /// query).
+    pub fn extend(&mut self, query: Query<FactKey, FactType>) {
+        if !self.expiries.is_empty() {
+            for fact in query.facts.keys() {
+                self.expiries.swap_remove(fact);
+            }
+        }
+
+        self.facts.extend(query.facts);
+        self.expiries.extend(query.expiries);
+    }
 }
 
 #[cfg(test)]
@@ -95,13 +222,13 @@ mod tests {
     #[test]
     fn new_query() {
         let query: Query<&str, i32> = Query::new();
-        assert_eq!(query.facts.len(), 0);
+        assert_eq!(query.facts().len(), 0);
     }
 
     #[test]
     fn new_query_with_capacity() {
         let query: Query<&str, i32> = Query::with_capacity(10);
-        assert_eq!(query.facts.len(), 0);
+        assert_eq!(query.facts().len(), 0);
     }
 
     #[test]
@@ -110,9 +237,9 @@ mod tests {
         query.insert("fact1", 1);
         query.insert("fact2", 2);
 
-        assert_eq!(query.facts.len(), 2);
-        assert_eq!(query.facts.get("fact1"), Some(&1));
-        assert_eq!(query.facts.get("fact2"), Some(&2));
+        assert_eq!(query.facts().len(), 2);
+        assert_eq!(query.facts().get("fact1"), Some(&1));
+        assert_eq!(query.facts().get("fact2"), Some(&2));
     }
 
     #[test]
@@ -121,8 +248,8 @@ mod tests {
         query.insert("fact1", 1);
         query.insert("fact1", 2);
 
-        assert_eq!(query.facts.len(), 1);
-        assert_eq!(query.facts.get("fact1"), Some(&2));
+        assert_eq!(query.facts().len(), 1);
+        assert_eq!(query.facts().get("fact1"), Some(&2));
     }
 
     #[test]
@@ -137,10 +264,119 @@ mod tests {
 
         query1.extend(query2);
 
-        assert_eq!(query1.facts.len(), 4);
-        assert_eq!(query1.facts.get("fact1"), Some(&1));
-        assert_eq!(query1.facts.get("fact2"), Some(&2));
-        assert_eq!(query1.facts.get("fact3"), Some(&3));
-        assert_eq!(query1.facts.get("fact4"), Some(&4));
+        assert_eq!(query1.facts().len(), 4);
+        assert_eq!(query1.facts().get("fact1"), Some(&1));
+        assert_eq!(query1.facts().get("fact2"), Some(&2));
+        assert_eq!(query1.facts().get("fact3"), Some(&3));
+        assert_eq!(query1.facts().get("fact4"), Some(&4));
+    }
+
+    #[test]
+    fn query_ttl_expiry() {
+        let mut query = Query::new();
+        query.insert("permanent", 1);
+        query.insert_with_ttl("just_died", 2, 5.);
+
+        assert_eq!(query.expiry(&"just_died"), Some(5.));
+        assert_eq!(query.expiry(&"permanent"), None);
+
+        query.tick(4.99);
+        assert_eq!(query.facts().len(), 2);
+
+        query.tick(5.);
+        assert_eq!(query.facts().len(), 1);
+        assert_eq!(query.facts().get("permanent"), Some(&1));
+        assert_eq!(query.expiry(&"just_died"), None);
+    }
+
+    #[test]
+    fn query_ttl_relative_to_now() {
+        let mut query = Query::new();
+        query.tick(10.);
+        query.insert_with_ttl("heard_gunshot", 1, 2.);
+
+        assert_eq!(query.expiry(&"heard_gunshot"), Some(12.));
+
+        query.tick(11.);
+        assert!(query.facts().contains_key("heard_gunshot"));
+
+        query.tick(12.5);
+        assert!(!query.facts().contains_key("heard_gunshot"));
+    }
+
+    #[test]
+    fn query_insertion_clears_ttl() {
+        let mut query = Query::new();
+        query.insert_with_ttl("fact1", 1, 1.);
+        query.insert("fact1", 2);
+
+        query.tick(2.);
+        assert_eq!(query.facts().get("fact1"), Some(&2));
+    }
+
+    #[test]
+    fn query_purge_expired() {
+        let mut query = Query::new();
+        query.insert_with_ttl("fact1", 1, 0.);
+        query.insert_with_ttl("fact2", 2, 1.);
+
+        query.purge_expired();
+        assert_eq!(query.facts().len(), 1);
+        assert_eq!(query.facts().get("fact2"), Some(&2));
+    }
+
+    #[test]
+    fn query_extension_retains_ttl() {
+        let mut query1 = Query::new();
+        query1.insert_with_ttl("fact1", 1, 1.);
+
+        let mut query2 = Query::new();
+        query2.insert("fact1", 2);
+        query2.insert_with_ttl("fact2", 3, 1.);
+
+        query1.extend(query2);
+        query1.tick(1.);
+
+        assert_eq!(query1.facts().len(), 1);
+        assert_eq!(query1.facts().get("fact1"), Some(&2));
+    }
+
+    #[test]
+    fn query_removal_clears_ttl() {
+        let mut query = Query::new();
+        query.insert_with_ttl("fact1", 1, 1.);
+        query.insert_with_ttl("fact2", 2, 1.);
+        query.insert("fact3", 3);
+
+        assert_eq!(query.remove("fact1"), Some(1));
+        assert_eq!(query.remove("fact1"), None);
+        assert_eq!(query.expiry(&"fact1"), None);
+
+        // Reinserting a removed fact doesn't inherit its old time-to-live
+        query.remove("fact2");
+        query.insert("fact2", 2);
+        assert_eq!(query.expiry(&"fact2"), None);
+
+        query.insert_with_ttl("fact4", 4, 1.);
+        query.clear();
+        assert!(query.facts().is_empty());
+        assert_eq!(query.expiry(&"fact4"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn query_ttl_serialization() {
+        let mut query = Query::new();
+        query.tick(3.);
+        query.insert_with_ttl("fact1".to_string(), 1, 2.);
+
+        let json = serde_json::to_string(&query).unwrap();
+        let mut query: Query<String, i32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(query.now(), 3.);
+        assert_eq!(query.expiry(&"fact1".to_string()), Some(5.));
+
+        query.tick(5.);
+        assert!(query.facts().is_empty());
     }
 }
@@ -0,0 +1,396 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::marker::PhantomData;
+
+use indexmap::IndexSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{evaluator::Evaluator, hash::FactHasher, query::Query, rule::Rule};
+
+/// A `FactId` is a dense integer identifier assigned to a fact key by a
+/// `FactKeyRegistry`.
+///
+/// Ids are assigned sequentially (starting at zero) in the order that keys are
+/// registered, so they can be used to index directly into arrays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FactId(u32);
+
+impl FactId {
+    /// Returns the id as an index into a dense array.
+    pub fn index(self) -> usize { self.0 as usize }
+}
+
+/// A `FactKeyRegistry` maps fact keys (e.g. `&str` names) to dense integer ids
+/// (`FactId`).
+///
+/// Keys are typically registered once, while your game loads its rules, after
+/// which queries and rules can be converted into their dense equivalents
+/// (`DenseQuery` and `DenseRule`). Evaluating a `DenseRule` against a
+/// `DenseQuery` replaces hashing each fact key with indexing into an array.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut registry = FactKeyRegistry::new();
+///
+/// let mut rule = Rule::new("You killed 5 enemies!");
+/// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+/// let rule = registry.dense_rule(rule);
+///
+/// // resolve ids once (e.g. during loading), then reuse them every frame
+/// let enemies_killed = registry.id(&"enemies_killed").unwrap();
+///
+/// let mut query = DenseQuery::new();
+/// query.insert(enemies_killed, 5.);
+///
+/// assert!(rule.evaluate(&query));
+/// # }
+/// ```
+///
+/// The registry serializes as a list of its (human-readable) keys, in id
+/// order. Dense queries and rules aren't serializable themselves: convert them
+/// back with `FactKeyRegistry::named_query` and `FactKeyRegistry::named_rule`
+/// before serializing.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct FactKeyRegistry<FactKey>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    keys: IndexSet<FactKey, FactHasher>,
+}
+
+impl<FactKey: core::hash::Hash + Eq> FactKeyRegistry<FactKey> {
+    /// Instantiates a new, empty instance of `FactKeyRegistry`.
+    pub fn new() -> Self {
+        Self {
+            keys: IndexSet::default(),
+        }
+    }
+
+    /// Registers a fact key, returning its id (or the existing id if the key
+    /// has already been registered).
+    ///
+    /// Computes in `O(1)` time (amortized average).
+    pub fn register(&mut self, key: FactKey) -> FactId {
+        let (index, _) = self.keys.insert_full(key);
+        FactId(index as u32)
+    }
+
+    /// Returns the id of a previously registered fact key.
+    ///
+    /// Computes in `O(1)` time.
+    pub fn id(&self, key: &FactKey) -> Option<FactId> {
+        self.keys
+            .get_index_of(key)
+            .map(|index| FactId(index as u32))
+    }
+
+    /// Returns the fact key that was registered with the provided id.
+    ///
+    /// Computes in `O(1)` time.
+    pub fn key(&self, id: FactId) -> Option<&FactKey> { self.keys.get_index(id.index()) }
+
+    /// Returns the number of registered fact keys.
+    pub fn len(&self) -> usize { self.keys.len() }
+
+    /// Returns `true` if no fact keys have been registered.
+    pub fn is_empty(&self) -> bool { self.keys.is_empty() }
+
+    /// Converts a rule into its dense equivalent, registering any fact keys
+    /// that haven't been seen before.
+    pub fn dense_rule<FactType, FactEvaluator, Outcome>(
+        &mut self,
+        rule: Rule<FactKey, FactType, FactEvaluator, Outcome>,
+    ) -> DenseRule<FactType, FactEvaluator, Outcome>
+    where
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+    {
+        let mut criteria: Vec<(FactId, FactEvaluator)> = rule
+            .evaluators
+            .into_iter()
+            .map(|(fact, evaluator)| (self.register(fact), evaluator))
+            .collect();
+        criteria.sort_unstable_by_key(|(id, _)| *id);
+
+        DenseRule {
+            marker: PhantomData,
+            name: rule.name,
+            criteria,
+            outcome: rule.outcome,
+        }
+    }
+
+    /// Converts a query into its dense equivalent.
+    ///
+    /// Facts with keys that haven't been registered are omitted, as no dense
+    /// rule can refer to them. Fact expiry (see `Query::insert_with_ttl`) is
+    /// not carried over.
+    pub fn dense_query<FactType: Copy>(
+        &self,
+        query: &Query<FactKey, FactType>,
+    ) -> DenseQuery<FactType> {
+        let mut dense = DenseQuery::with_capacity(self.len());
+
+        for (fact, value) in query.facts() {
+            if let Some(id) = self.id(fact) {
+                dense.insert(id, *value);
+            }
+        }
+
+        dense
+    }
+
+    /// Converts a dense rule back into a rule keyed by the registered fact
+    /// keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule refers to an id that wasn't assigned by this
+    /// registry.
+    pub fn named_rule<FactType, FactEvaluator, Outcome>(
+        &self,
+        rule: DenseRule<FactType, FactEvaluator, Outcome>,
+    ) -> Rule<FactKey, FactType, FactEvaluator, Outcome>
+    where
+        FactKey: Clone,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+    {
+        let mut named = Rule::new(rule.outcome);
+        named.name = rule.name;
+
+        for (id, evaluator) in rule.criteria {
+            named.insert(self.expect_key(id).clone(), evaluator);
+        }
+
+        named
+    }
+
+    /// Converts a dense query back into a query keyed by the registered fact
+    /// keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query contains an id that wasn't assigned by this
+    /// registry.
+    pub fn named_query<FactType: Copy>(
+        &self,
+        query: &DenseQuery<FactType>,
+    ) -> Query<FactKey, FactType>
+    where
+        FactKey: Clone,
+    {
+        let mut named = Query::new();
+
+        for (id, value) in query.iter() {
+            named.insert(self.expect_key(id).clone(), value);
+        }
+
+        named
+    }
+
+    fn expect_key(&self, id: FactId) -> &FactKey {
+        self.key(id)
+            .expect("fact id was not assigned by this registry")
+    }
+}
+
+/// A `DenseQuery<FactType>` is the equivalent of a `Query` whose facts are
+/// identified by `FactId` (assigned by a `FactKeyRegistry`).
+///
+/// Facts are stored in a vector indexed by id, so looking up a fact's value is
+/// a single array access.
+#[derive(Clone, Debug, Default)]
+pub struct DenseQuery<FactType> {
+    values: Vec<Option<FactType>>,
+}
+
+impl<FactType: Copy> DenseQuery<FactType> {
+    /// Instantiates a new instance of `DenseQuery` without allocating.
+    ///
+    /// Computes in `O(1)` time.
+    pub fn new() -> Self { Self { values: Vec::new() } }
+
+    /// Instantiates a new instance of `DenseQuery` with space for facts with
+    /// ids up to (but excluding) `capacity`.
+    ///
+    /// Computes in `O(n)` time.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: vec![None; capacity],
+        }
+    }
+
+    /// Inserts a fact into the query, overwriting any existing value.
+    ///
+    /// Computes in `O(1)` time (unless the query needs to grow to fit the id).
+    pub fn insert(&mut self, fact: FactId, value: FactType) {
+        let index = fact.index();
+
+        if index >= self.values.len() {
+            self.values.resize(index + 1, None);
+        }
+
+        self.values[index] = Some(value);
+    }
+
+    /// Removes a fact from the query, returning its value (if present).
+    ///
+    /// Computes in `O(1)` time.
+    pub fn remove(&mut self, fact: FactId) -> Option<FactType> {
+        self.values.get_mut(fact.index()).and_then(Option::take)
+    }
+
+    /// Returns the value of a fact in the query (if present).
+    ///
+    /// Computes in `O(1)` time.
+    pub fn get(&self, fact: FactId) -> Option<FactType> {
+        self.values.get(fact.index()).copied().flatten()
+    }
+
+    /// Removes all facts from the query (retaining its allocation).
+    pub fn clear(&mut self) { self.values.iter_mut().for_each(|value| *value = None); }
+
+    /// Returns an iterator over the facts (and their values) in the query, in
+    /// id order.
+    pub fn iter(&self) -> impl Iterator<Item = (FactId, FactType)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| value.map(|value| (FactId(index as u32), value)))
+    }
+}
+
+/// A `DenseRule` is the equivalent of a `Rule` whose evaluators are keyed by
+/// `FactId` (assigned by a `FactKeyRegistry`).
+///
+/// Evaluators are stored in a small array sorted by id (rather than a map).
+/// Because ids are assigned in registration order, facts registered first
+/// (e.g. "namespace" facts such as the current speaker) are still evaluated
+/// first.
+#[derive(Clone, Debug)]
+pub struct DenseRule<FactType, FactEvaluator: Evaluator<FactType>, Outcome> {
+    marker: PhantomData<fn() -> FactType>,
+    /// An optional name that identifies the rule (see `Rule::name`).
+    pub name: Option<String>,
+    criteria: Vec<(FactId, FactEvaluator)>,
+    /// The outcome of the rule that's returned during evaluation if the rule
+    /// matches the supplied `DenseQuery` instance.
+    pub outcome: Outcome,
+}
+
+impl<FactType: Copy, FactEvaluator: Evaluator<FactType> + Copy, Outcome>
+    DenseRule<FactType, FactEvaluator, Outcome>
+{
+    /// Returns the rule's evaluators (and the ids of the facts they evaluate),
+    /// sorted by id.
+    pub fn criteria(&self) -> &[(FactId, FactEvaluator)] { &self.criteria }
+
+    /// Evaluates the rule against the provided dense query.
+    ///
+    /// Returns `true` if all facts in the rule are present in the query and all
+    /// fact evaluators resolve to `true`, otherwise returns `false`.
+    ///
+    /// Computes in `O(n)` time (worst case). This is dependent on your
+    /// evaluator implementation evaluating in a constant time.
+    pub fn evaluate(&self, query: &DenseQuery<FactType>) -> bool {
+        self.criteria.iter().all(|(fact, evaluator)| {
+            query
+                .get(*fact)
+                .is_some_and(|value| evaluator.evaluate(value))
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use super::*;
+    use crate::float::FloatEvaluator;
+
+    #[test]
+    fn registry_ids_are_dense() {
+        let mut registry = FactKeyRegistry::new();
+
+        assert_eq!(registry.register("fact1"), FactId(0));
+        assert_eq!(registry.register("fact2"), FactId(1));
+        assert_eq!(registry.register("fact1"), FactId(0));
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.id(&"fact2"), Some(FactId(1)));
+        assert_eq!(registry.id(&"fact3"), None);
+        assert_eq!(registry.key(FactId(0)), Some(&"fact1"));
+    }
+
+    #[test]
+    fn dense_rule_evaluation() {
+        let mut registry = FactKeyRegistry::new();
+
+        let mut rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        rule.insert("doors_opened", FloatEvaluator::gt(2.));
+        let rule = registry.dense_rule(rule);
+
+        let mut query = Query::new();
+        query.insert("enemies_killed", 2.5 + 1.5 + 1.);
+        query.insert("doors_opened", 10.);
+        query.insert("unrelated", 1.);
+        let mut query = registry.dense_query(&query);
+
+        assert!(rule.evaluate(&query));
+
+        query.remove(registry.id(&"doors_opened").unwrap());
+        assert!(!rule.evaluate(&query));
+    }
+
+    #[test]
+    fn dense_query_insertion() {
+        let mut query = DenseQuery::new();
+        query.insert(FactId(3), 1.);
+
+        assert_eq!(query.get(FactId(3)), Some(1.));
+        assert_eq!(query.get(FactId(0)), None);
+        assert_eq!(query.get(FactId(10)), None);
+
+        query.clear();
+        assert_eq!(query.iter().count(), 0);
+    }
+
+    #[test]
+    fn named_round_trip() {
+        let mut registry = FactKeyRegistry::new();
+
+        let mut rule = Rule::named("rule1", true);
+        rule.insert("fact1", FloatEvaluator::lt(5.));
+        let rule = registry.dense_rule(rule);
+        let rule = registry.named_rule(rule);
+
+        assert_eq!(rule.name.as_deref(), Some("rule1"));
+        assert_eq!(rule.evaluators.get("fact1"), Some(&FloatEvaluator::lt(5.)));
+
+        let mut query = Query::new();
+        query.insert("fact1", 1.);
+        let query = registry.named_query(&registry.dense_query(&query));
+
+        assert_eq!(query.facts().get("fact1"), Some(&1.));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn registry_serializes_names() {
+        let mut registry = FactKeyRegistry::new();
+        registry.register("fact1".to_string());
+        registry.register("fact2".to_string());
+
+        let json = serde_json::to_string(&registry).unwrap();
+        assert_eq!(json, r#"["fact1","fact2"]"#);
+
+        let registry: FactKeyRegistry<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(registry.id(&"fact2".to_string()), Some(FactId(1)));
+    }
+}
@@ -1,30 +1,44 @@
-use std::marker::PhantomData;
+use alloc::{string::String, vec::Vec};
+use core::marker::PhantomData;
 
 use indexmap::IndexMap;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{evaluator::Evaluator, query::Query};
+use crate::{evaluator::Evaluator, hash::FactHasher, query::Query};
 
 /// A `Rule` is a collection of facts and their evaluators (requirements) stored
 /// in a map, along with a specific outcome (`Outcome`). All evaluators in a
 /// rule must evaluate to `true` for the rule itself to be considered `true`.
+///
+/// When the `serde` feature is enabled, rules are (de)serialized as a map with
+/// an optional `name`, a map of `criteria` (fact keys to evaluators) and an
+/// `outcome`.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rule<FactKey, FactType, FactEvaluator: Evaluator<FactType>, Outcome>
 where
-    FactKey: std::hash::Hash + Eq,
+    FactKey: core::hash::Hash + Eq,
 {
-    marker: PhantomData<FactType>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    marker: PhantomData<fn() -> FactType>,
+    /// An optional name that identifies the rule (e.g. in tooling, or when
+    /// debugging which rule was matched).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub name: Option<String>,
     /// The map of facts and evaluators that will be used to evaluate each
     /// fact's value.
-    pub evaluators: IndexMap<FactKey, FactEvaluator>,
+    #[cfg_attr(feature = "serde", serde(rename = "criteria"))]
+    pub evaluators: IndexMap<FactKey, FactEvaluator, FactHasher>,
     /// The outcome of the rule that's returned during evaluation if the rule
     /// matches the supplied `Query` instance.
     pub outcome: Outcome,
 }
 
 impl<
-        FactKey: std::hash::Hash + Eq,
+        FactKey: core::hash::Hash + Eq,
         FactType: Copy,
         FactEvaluator: Evaluator<FactType> + Copy,
         Outcome,
@@ -37,11 +51,22 @@ impl<
     pub fn new(outcome: Outcome) -> Self {
         Self {
             marker: PhantomData,
-            evaluators: IndexMap::new(),
+            name: None,
+            evaluators: IndexMap::default(),
             outcome,
         }
     }
 
+    /// Instantiates a new, named instance of `Rule` without allocating an
+    /// underlying collection of evaluators.
+    ///
+    /// Computes in `O(1)` time.
+    pub fn named(name: impl Into<String>, outcome: Outcome) -> Self {
+        let mut rule = Self::new(outcome);
+        rule.name = Some(name.into());
+        rule
+    }
+
     /// Inserts a new evaluator for a specific fact key into the rule.
     ///
     /// Computes in `O(1)` time (amortized average, depending on current
@@ -60,7 +85,7 @@ impl<
     pub fn evaluate(&self, query: &Query<FactKey, FactType>) -> bool {
         // IndexMap::len() has a time complexity of O(1), so we check this
         // against the query's length to avoid unnecessary iteration
-        if self.evaluators.len() > query.facts.len() {
+        if self.evaluators.len() > query.facts().len() {
             return false;
         }
 
@@ -68,7 +93,7 @@ impl<
         // in the query or evaluates to false, break out of the loop
         // and return false
         for (fact, evaluator) in &self.evaluators {
-            if let Some(fact_value) = query.facts.get(fact) {
+            if let Some(fact_value) = query.facts().get(fact) {
                 if !evaluator.evaluate(*fact_value) {
                     return false;
                 }
@@ -81,6 +106,59 @@ impl<
         // to true, so the rule is true for the provided query
         true
     }
+
+    /// Returns each of the rule's criteria that fail for the provided query
+    /// (because the query doesn't contain the fact, or the fact's evaluator
+    /// resolves to `false`), in the order they were inserted into the rule.
+    ///
+    /// The rule matches the query if no criteria fail.
+    pub fn failures(
+        &self,
+        query: &Query<FactKey, FactType>,
+    ) -> Vec<CriterionFailure<'_, FactKey, FactType, FactEvaluator>> {
+        self.failures_within(query, usize::MAX).unwrap_or_default()
+    }
+
+    /// Returns each of the rule's criteria that fail for the provided query,
+    /// or `None` if more than `max` criteria fail.
+    pub(crate) fn failures_within(
+        &self,
+        query: &Query<FactKey, FactType>,
+        max: usize,
+    ) -> Option<Vec<CriterionFailure<'_, FactKey, FactType, FactEvaluator>>> {
+        let mut failures = Vec::new();
+
+        for (fact, evaluator) in &self.evaluators {
+            let value = query.facts().get(fact).copied();
+
+            if !value.is_some_and(|value| evaluator.evaluate(value)) {
+                if failures.len() == max {
+                    return None;
+                }
+
+                failures.push(CriterionFailure {
+                    fact,
+                    evaluator,
+                    value,
+                });
+            }
+        }
+
+        Some(failures)
+    }
+}
+
+/// A criterion in a rule (a fact and its evaluator) that fails for a query, as
+/// returned by `Rule::failures`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CriterionFailure<'a, FactKey, FactType, FactEvaluator> {
+    /// The fact checked by the criterion.
+    pub fact: &'a FactKey,
+    /// The fact's evaluator.
+    pub evaluator: &'a FactEvaluator,
+    /// The fact's value in the query, or `None` if the query doesn't contain
+    /// the fact.
+    pub value: Option<FactType>,
 }
 
 #[cfg(test)]
@@ -112,4 +190,58 @@ mod tests {
 
         assert!(rule.evaluate(&query));
     }
+
+    #[test]
+    fn rule_failures() {
+        let mut rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        rule.insert("doors_opened", FloatEvaluator::gt(2.));
+
+        let mut query = Query::new();
+        query.insert("enemies_killed", 4.);
+
+        assert_eq!(
+            rule.failures(&query),
+            [
+                CriterionFailure {
+                    fact: &"enemies_killed",
+                    evaluator: &FloatEvaluator::EqualTo(5.),
+                    value: Some(4.),
+                },
+                CriterionFailure {
+                    fact: &"doors_opened",
+                    evaluator: &FloatEvaluator::gt(2.),
+                    value: None,
+                },
+            ]
+        );
+
+        query.insert("enemies_killed", 5.);
+        query.insert("doors_opened", 3.);
+        assert!(rule.failures(&query).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn rule_serialization() {
+        let mut rule = Rule::named("doors", "You opened some doors!");
+        rule.insert("doors_opened", FloatEvaluator::range(2., 5.));
+
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"doors","criteria":{"doors_opened":{"gte":2.0,"lt":5.0}},"outcome":"You opened some doors!"}"#
+        );
+
+        let rule: Rule<&str, f64, FloatEvaluator, &str> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rule.name.as_deref(), Some("doors"));
+        assert_eq!(
+            rule.evaluators.get("doors_opened"),
+            Some(&FloatEvaluator::range(2., 5.))
+        );
+
+        let rule: Rule<&str, f64, FloatEvaluator, bool> =
+            serde_json::from_str(r#"{"criteria":{},"outcome":true}"#).unwrap();
+        assert_eq!(rule.name, None);
+    }
 }
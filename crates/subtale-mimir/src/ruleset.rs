@@ -1,8 +1,31 @@
-use rand::seq::SliceRandom;
+use alloc::vec::Vec;
+#[cfg(feature = "binary")]
+use alloc::{string::String, vec};
+use core::{cmp::Reverse, mem};
+
+#[cfg(feature = "binary")]
+use indexmap::IndexMap;
+#[cfg(feature = "rayon")]
+use rand::SeedableRng;
+use rand::{seq::SliceRandom, Rng};
+#[cfg(feature = "rayon")]
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{evaluator::Evaluator, query::Query, rule::Rule};
+#[cfg(feature = "binary")]
+use crate::{
+    binary::{self, BinaryError, Kind},
+    hash::FactHasher,
+};
+use crate::{
+    cache::{self, CriterionCache},
+    evaluator::Evaluator,
+    query::Query,
+    rule::{CriterionFailure, Rule},
+};
 
 /// A `Ruleset` is a collection of `Rule` instances, represented as a
 /// `Vec<Rule<...>>`.
@@ -20,81 +43,517 @@ use crate::{evaluator::Evaluator, query::Query, rule::Rule};
 /// for each level/map/region of your game. Otherwise, you'll be subjecting
 /// yourself to an unnecessary performance cost by having Mímir evaluate rules
 /// that have no relevance to the game's current state.
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+///
+/// Rulesets are `Send` and `Sync` whenever their generic types are, so a single
+/// ruleset can be shared between threads (e.g. behind an `Arc`). Use
+/// `Ruleset::evaluate_with_rng` with a generator owned by each thread if you
+/// need reproducible results.
+///
+/// When the `serde` feature is enabled, rulesets are (de)serialized as a plain
+/// list of rules (and sorted by specificity when deserialized).
+///
+/// Rules frequently share identical criteria (e.g. the current speaker). If the
+/// ruleset is created with `Ruleset::with_shared_criteria`, these are detected
+/// up front and each shared criterion is evaluated at most once per query (see
+/// `CriterionCache`).
 pub struct Ruleset<FactKey, FactType, FactEvaluator: Evaluator<FactType>, Outcome>
 where
-    FactKey: std::hash::Hash + Eq,
+    FactKey: core::hash::Hash + Eq,
 {
     rules: Vec<Rule<FactKey, FactType, FactEvaluator, Outcome>>,
+    /// The position of each rule (in the order of the rules) in the
+    /// collection the ruleset was created from.
+    positions: Vec<usize>,
+    /// The cache slot of each rule's criteria (in the order of the rules), or
+    /// empty if no criteria are shared between rules.
+    slots: Vec<u32>,
+}
+
+impl<
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy + core::hash::Hash + PartialEq,
+        Outcome,
+    > Ruleset<FactKey, FactType, FactEvaluator, Outcome>
+{
+    /// Creates a new ruleset from the provided collection of rules, detecting
+    /// the criteria shared between rules (the same fact key and an equal
+    /// evaluator) so that each is evaluated at most once per query.
+    ///
+    /// Computes in `O(n log n + m)` time (where `n` is the number of rules,
+    /// and `m` is the total number of evaluators across all rules).
+    pub fn with_shared_criteria(
+        rules: Vec<Rule<FactKey, FactType, FactEvaluator, Outcome>>,
+    ) -> Self {
+        let mut new = Self::new(rules);
+        new.share_criteria();
+        new
+    }
+
+    /// Detects the criteria shared between the ruleset's rules (as with
+    /// `Ruleset::with_shared_criteria`), e.g. after appending rules to the
+    /// ruleset.
+    ///
+    /// Computes in `O(m)` expected time (where `m` is the total number of
+    /// evaluators across all rules).
+    pub fn share_criteria(&mut self) {
+        let ids = cache::dedup(self.rules.iter().flat_map(|rule| &rule.evaluators));
+        let slots = cache::slots(&ids, ids.iter().max().map_or(0, |id| id + 1));
+
+        // Without any shared criteria, there's nothing worth caching
+        self.slots = if slots.iter().any(|slot| *slot != cache::UNSHARED) {
+            ids.into_iter().map(|id| slots[id]).collect()
+        } else {
+            Vec::new()
+        };
+    }
 }
 
 impl<
-        FactKey: std::hash::Hash + Eq,
+        FactKey: core::hash::Hash + Eq,
         FactType: Copy,
         FactEvaluator: Evaluator<FactType> + Copy,
         Outcome,
     > Ruleset<FactKey, FactType, FactEvaluator, Outcome>
 {
     fn sort(&mut self) {
-        self.rules
-            .sort_unstable_by_key(|x| -(x.evaluators.len() as isize));
+        // A stable sort keeps rules with the same specificity in their original
+        // order (along with their positions)
+        let mut rules: Vec<_> = mem::take(&mut self.positions)
+            .into_iter()
+            .zip(mem::take(&mut self.rules))
+            .collect();
+
+        rules.sort_by_key(|(_, rule)| Reverse(rule.evaluators.len()));
+        (self.positions, self.rules) = rules.into_iter().unzip();
+        self.slots.clear();
     }
 
     /// Creates a new ruleset from the provided collection of rules.
+    ///
+    /// Computes in `O(n log n)` time.
     pub fn new(rules: Vec<Rule<FactKey, FactType, FactEvaluator, Outcome>>) -> Self {
-        let mut new = Self { rules };
+        let mut new = Self {
+            positions: (0..rules.len()).collect(),
+            rules,
+            slots: Vec::new(),
+        };
         new.sort();
         new
     }
 
     /// Appends all rules from another ruleset into the ruleset.
+    ///
+    /// Criteria are no longer shared between the ruleset's rules afterwards
+    /// (call `Ruleset::share_criteria` to detect them again).
+    ///
+    /// The appended rules are positioned after the ruleset's existing rules
+    /// (see `Ruleset::position`).
     pub fn append(&mut self, ruleset: &mut Ruleset<FactKey, FactType, FactEvaluator, Outcome>) {
+        let offset = self.rules.len();
+        self.positions.extend(
+            ruleset
+                .positions
+                .drain(..)
+                .map(|position| position + offset),
+        );
         self.rules.append(&mut ruleset.rules);
+        ruleset.slots.clear();
         self.sort();
     }
 
+    /// Returns the rules in the ruleset (in descending order of specificity).
+    pub fn rules(&self) -> &[Rule<FactKey, FactType, FactEvaluator, Outcome>] { &self.rules }
+
+    /// Returns the position of the rule at the provided index (in
+    /// `Ruleset::rules`) in the collection the ruleset was created from (e.g.
+    /// the order of the rules in a file), which is how unnamed rules should be
+    /// identified to people editing them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn position(&self, index: usize) -> usize { self.positions[index] }
+
+    /// Consumes the ruleset, returning its rules (in descending order of
+    /// specificity).
+    pub(crate) fn into_rules(self) -> Vec<Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        self.rules
+    }
+
     /// Evaluates the ruleset against the provided query.
     ///
     /// Returns the most specific (most evaluators) rule in the ruleset that
     /// evaluates to true for the provided query. If multiple rules evaluate
     /// to true with the same specificness, they are all returned.
+    ///
+    /// If the ruleset shares criteria between rules (see
+    /// `Ruleset::with_shared_criteria`), a new cache is created for each
+    /// query, so prefer `Ruleset::evaluate_all_with_cache` when evaluating
+    /// queries frequently.
     pub fn evaluate_all(
         &self,
         query: &Query<FactKey, FactType>,
+    ) -> Vec<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        if self.slots.is_empty() {
+            return self.matches(query, |_, fact, evaluator| {
+                Self::check(query, fact, evaluator)
+            });
+        }
+
+        self.evaluate_all_with_cache(query, &mut CriterionCache::new())
+    }
+
+    /// Evaluates the ruleset against the provided query, caching the results
+    /// of shared criteria in the provided cache (which can be reused between
+    /// queries to avoid allocating a new cache each time).
+    ///
+    /// Returns the same rules as `Ruleset::evaluate_all`.
+    pub fn evaluate_all_with_cache(
+        &self,
+        query: &Query<FactKey, FactType>,
+        cache: &mut CriterionCache,
+    ) -> Vec<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        cache.reset();
+
+        self.matches(query, |criterion, fact, evaluator| {
+            let slot = self.slots.get(criterion).copied();
+
+            cache.get_or_insert_with(slot.unwrap_or(cache::UNSHARED), || {
+                Self::check(query, fact, evaluator)
+            })
+        })
+    }
+
+    /// Returns the most specific rules whose criteria are all satisfied, where
+    /// `evaluate` checks a single criterion (given its index among the
+    /// criteria of all rules, its fact and its evaluator).
+    fn matches(
+        &self,
+        query: &Query<FactKey, FactType>,
+        mut evaluate: impl FnMut(usize, &FactKey, &FactEvaluator) -> bool,
     ) -> Vec<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
         let mut matched = Vec::<&Rule<FactKey, FactType, FactEvaluator, Outcome>>::new();
+        let mut offset = 0;
 
         for rule in self.rules.iter() {
-            if matched.first().map_or(0, |x| x.evaluators.len()) <= rule.evaluators.len() {
-                if rule.evaluate(query) {
-                    matched.push(rule);
-                }
-            } else {
+            let specificity = rule.evaluators.len();
+
+            if matched.first().map_or(0, |x| x.evaluators.len()) > specificity {
                 break;
             }
+
+            let first = offset;
+            offset += specificity;
+
+            // IndexMap::len() has a time complexity of O(1), so we check this
+            // against the query's length to avoid unnecessary iteration
+            if specificity > query.facts().len() {
+                continue;
+            }
+
+            let evaluated = rule
+                .evaluators
+                .iter()
+                .enumerate()
+                .all(|(index, (fact, evaluator))| evaluate(first + index, fact, evaluator));
+
+            if evaluated {
+                matched.push(rule);
+            }
         }
 
         matched
     }
 
+    /// Returns `true` if the query contains the fact and its value satisfies
+    /// the evaluator.
+    fn check(query: &Query<FactKey, FactType>, fact: &FactKey, evaluator: &FactEvaluator) -> bool {
+        query
+            .facts()
+            .get(fact)
+            .is_some_and(|value| evaluator.evaluate(*value))
+    }
+
     /// Evaluates the ruleset against the provided query.
     ///
     /// Returns the most specific (most evaluators) rule in the ruleset that
     /// evaluates to true for the provided query. If multiple rules evaluate
     /// to true with the same specificness, one is picked at random.
+    #[cfg(feature = "std")]
     pub fn evaluate(
         &self,
         query: &Query<FactKey, FactType>,
+    ) -> Option<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
+        self.evaluate_with_rng(query, &mut rand::thread_rng())
+    }
+
+    /// Evaluates the ruleset against the provided query.
+    ///
+    /// Behaves the same as `Ruleset::evaluate`, except that ties between
+    /// equally specific rules are broken with the provided random number
+    /// generator (rather than the thread-local generator), which allows for
+    /// reproducible results (e.g. using a seeded generator per thread).
+    ///
+    /// Unlike `Ruleset::evaluate`, this is available without the `std`
+    /// feature.
+    pub fn evaluate_with_rng<R: Rng + ?Sized>(
+        &self,
+        query: &Query<FactKey, FactType>,
+        rng: &mut R,
     ) -> Option<&Rule<FactKey, FactType, FactEvaluator, Outcome>> {
         let matched = self.evaluate_all(query);
-        matched.choose(&mut rand::thread_rng()).copied()
+        matched.choose(rng).copied()
+    }
+
+    /// Returns the rules that fail to match the provided query by at least one
+    /// and at most `max_failures` criteria (along with the criteria that fail),
+    /// in the order of the ruleset's rules.
+    ///
+    /// This is useful for tooling that suggests which facts would need to
+    /// change for a rule to match (e.g. when tuning content). Rules that match
+    /// the query aren't returned.
+    ///
+    /// ```
+    /// # #[cfg(feature = "float")] {
+    /// use subtale_mimir::prelude::*;
+    ///
+    /// let mut rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+    /// rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+    /// rule.insert("doors_opened", FloatEvaluator::gte(2.));
+    ///
+    /// let ruleset = Ruleset::new(vec![rule]);
+    ///
+    /// let mut query = Query::new();
+    /// query.insert("enemies_killed", 5.);
+    ///
+    /// let near_misses = ruleset.near_misses(&query, 1);
+    /// assert_eq!(near_misses[0].failures[0].fact, &"doors_opened");
+    /// assert_eq!(near_misses[0].failures[0].value, None);
+    /// # }
+    /// ```
+    pub fn near_misses(
+        &self,
+        query: &Query<FactKey, FactType>,
+        max_failures: usize,
+    ) -> Vec<NearMiss<'_, FactKey, FactType, FactEvaluator, Outcome>> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| {
+                let failures = rule.failures_within(query, max_failures)?;
+
+                (!failures.is_empty()).then_some(NearMiss {
+                    index,
+                    rule,
+                    failures,
+                })
+            })
+            .collect()
+    }
+
+    /// Evaluates the ruleset against each of the provided queries in parallel
+    /// (using `rayon`), returning the result for each query in the same order.
+    ///
+    /// Ties between equally specific rules are broken with a random number
+    /// generator (a `ChaCha8Rng`) seeded from `seed` and the query's index in
+    /// `queries`, so evaluating the same batch with the same seed always
+    /// returns the same results (regardless of how the work is scheduled
+    /// across threads, or the platform and version of `rand`).
+    ///
+    /// Each query's generator is seeded with the SplitMix64 finalizer of
+    /// `seed ^ (index * 0x9E37_79B9_7F4A_7C15)`, so batches evaluated with
+    /// adjacent seeds don't share generators (e.g. the second query of a
+    /// batch with seed `0` and the first query of a batch with seed `1`).
+    #[cfg(feature = "rayon")]
+    pub fn evaluate_batch(
+        &self,
+        queries: &[&Query<FactKey, FactType>],
+        seed: u64,
+    ) -> Vec<Option<&Rule<FactKey, FactType, FactEvaluator, Outcome>>>
+    where
+        FactKey: Sync,
+        FactType: Sync,
+        FactEvaluator: Sync,
+        Outcome: Sync,
+    {
+        queries
+            .par_iter()
+            .enumerate()
+            .map(|(index, query)| {
+                let mut rng = ChaCha8Rng::seed_from_u64(query_seed(seed, index));
+                self.evaluate_with_rng(query, &mut rng)
+            })
+            .collect()
+    }
+}
+
+/// Returns the seed of the generator used to break ties for the query at the
+/// provided index of a batch (see `Ruleset::evaluate_batch`).
+#[cfg(feature = "rayon")]
+fn query_seed(seed: u64, index: usize) -> u64 {
+    let mut z = seed ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A rule that fails to match a query by only a few criteria, as returned by
+/// `Ruleset::near_misses`.
+pub struct NearMiss<'a, FactKey, FactType, FactEvaluator: Evaluator<FactType>, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    /// The index of the rule in the ruleset (in the order of
+    /// `Ruleset::rules`).
+    pub index: usize,
+    /// The rule that failed to match the query.
+    pub rule: &'a Rule<FactKey, FactType, FactEvaluator, Outcome>,
+    /// The rule's criteria that fail for the query.
+    pub failures: Vec<CriterionFailure<'a, FactKey, FactType, FactEvaluator>>,
+}
+
+/// The representation of a rule in Mímir's binary format (its name, evaluators
+/// and outcome).
+#[cfg(feature = "binary")]
+type EncodedRule<FactKey, FactEvaluator, Outcome> = (
+    Option<String>,
+    IndexMap<FactKey, FactEvaluator, FactHasher>,
+    Outcome,
+);
+
+/// The representation of a ruleset in Mímir's binary format (its rules, the
+/// cache slot of each criterion, and the position of each rule).
+#[cfg(feature = "binary")]
+type EncodedRuleset<FactKey, FactEvaluator, Outcome> = (
+    Vec<EncodedRule<FactKey, FactEvaluator, Outcome>>,
+    Vec<u32>,
+    Vec<usize>,
+);
+
+#[cfg(feature = "binary")]
+impl<
+        FactKey: core::hash::Hash + Eq,
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy,
+        Outcome,
+    > Ruleset<FactKey, FactType, FactEvaluator, Outcome>
+{
+    /// Encodes the ruleset in Mímir's compact binary format.
+    ///
+    /// The rules are stored in their sorted order (along with their positions,
+    /// and the criteria shared between them if the ruleset was created with
+    /// `Ruleset::with_shared_criteria`), so loading the ruleset with
+    /// `Ruleset::from_bytes` doesn't need to sort them or detect shared
+    /// criteria again.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BinaryError>
+    where
+        FactKey: Serialize,
+        FactEvaluator: Serialize,
+        Outcome: Serialize,
+    {
+        let rules: Vec<_> = self
+            .rules
+            .iter()
+            .map(|rule| (&rule.name, &rule.evaluators, &rule.outcome))
+            .collect();
+
+        binary::to_bytes(Kind::Ruleset, &(rules, &self.slots, &self.positions))
+    }
+
+    /// Decodes a ruleset from bytes written by `Ruleset::to_bytes`.
+    ///
+    /// Fact keys and outcomes that borrow from the input (e.g. `&str`) aren't
+    /// copied, so the bytes can be loaded directly from a memory-mapped file.
+    pub fn from_bytes<'a>(bytes: &'a [u8]) -> Result<Self, BinaryError>
+    where
+        FactKey: Deserialize<'a>,
+        FactEvaluator: Deserialize<'a>,
+        Outcome: Deserialize<'a>,
+    {
+        let (rules, slots, positions): EncodedRuleset<FactKey, FactEvaluator, Outcome> =
+            binary::from_bytes(Kind::Ruleset, bytes)?;
+
+        if rules
+            .windows(2)
+            .any(|rules| rules[0].1.len() < rules[1].1.len())
+        {
+            return Err(BinaryError::Corrupt);
+        }
+
+        // Every criterion must have a slot (unless none are shared), and slots
+        // can't exceed the number of criteria (so that caching their results
+        // can't allocate more than evaluating them)
+        let criteria = rules.iter().map(|rule| rule.1.len()).sum();
+
+        if !(slots.is_empty() || slots.len() == criteria)
+            || slots
+                .iter()
+                .any(|slot| *slot != cache::UNSHARED && *slot as usize >= criteria)
+        {
+            return Err(BinaryError::Corrupt);
+        }
+
+        // Each rule must have a distinct position within the ruleset
+        let mut positioned = vec![false; rules.len()];
+
+        if positions.len() != rules.len()
+            || positions.iter().any(|&position| {
+                position >= rules.len() || mem::replace(&mut positioned[position], true)
+            })
+        {
+            return Err(BinaryError::Corrupt);
+        }
+
+        let rules = rules
+            .into_iter()
+            .map(|(name, evaluators, outcome)| {
+                let mut rule = Rule::new(outcome);
+                rule.name = name;
+                rule.evaluators = evaluators;
+                rule
+            })
+            .collect();
+
+        Ok(Self {
+            rules,
+            positions,
+            slots,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<FactKey, FactType, FactEvaluator, Outcome> Serialize
+    for Ruleset<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+    FactEvaluator: Evaluator<FactType>,
+    Rule<FactKey, FactType, FactEvaluator, Outcome>: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.rules.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, FactKey, FactType, FactEvaluator, Outcome> Deserialize<'de>
+    for Ruleset<FactKey, FactType, FactEvaluator, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy,
+    Rule<FactKey, FactType, FactEvaluator, Outcome>: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::new)
     }
 }
 
 #[cfg(test)]
 #[cfg(feature = "float")]
 mod tests {
-    use crate::prelude::*;
+    use crate::{prelude::*, testing::*};
 
     #[test]
     fn ruleset_evaluation() {
@@ -124,4 +583,256 @@ mod tests {
             "You killed 5 enemies and opened 2 doors!"
         );
     }
+
+    #[test]
+    fn ruleset_near_misses() {
+        let mut rule = Rule::new("You killed 5 enemies!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+
+        let mut more_specific_rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+        more_specific_rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened", FloatEvaluator::gt(2.));
+
+        let ruleset = Ruleset::new(vec![rule, more_specific_rule]);
+
+        let mut query = Query::new();
+        query.insert("doors_opened", 1.);
+
+        let near_misses = ruleset.near_misses(&query, 1);
+        assert_eq!(near_misses.len(), 1);
+        assert_eq!(near_misses[0].index, 1);
+        assert_eq!(near_misses[0].rule.outcome, "You killed 5 enemies!");
+        assert_eq!(near_misses[0].failures[0].fact, &"enemies_killed");
+
+        let near_misses = ruleset.near_misses(&query, 2);
+        assert_eq!(near_misses.len(), 2);
+        assert_eq!(near_misses[0].failures.len(), 2);
+
+        // Rules that match aren't near misses
+        query.insert("enemies_killed", 5.);
+        let near_misses = ruleset.near_misses(&query, 2);
+        assert_eq!(near_misses.len(), 1);
+        assert_eq!(near_misses[0].failures[0].value, Some(1.));
+        assert!(ruleset.near_misses(&query, 0).is_empty());
+    }
+
+    #[test]
+    fn ruleset_shared_between_threads() {
+        use std::{sync::Arc, thread};
+
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let rules = (0..10)
+            .map(|outcome| {
+                let mut rule = Rule::new(outcome);
+                rule.insert("enemies_killed", FloatEvaluator::gte(0.));
+                rule
+            })
+            .collect();
+
+        let ruleset = Arc::new(Ruleset::new(rules));
+
+        let mut query = Query::new();
+        query.insert("enemies_killed", 5.);
+        let query = Arc::new(query);
+
+        let evaluate = |seed| {
+            let ruleset = Arc::clone(&ruleset);
+            let query = Arc::clone(&query);
+
+            thread::spawn(move || {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..20)
+                    .map(|_| ruleset.evaluate_with_rng(&query, &mut rng).unwrap().outcome)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let handles: Vec<_> = (0..4).map(|_| evaluate(7)).collect();
+        let results: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn ruleset_batch_evaluation() {
+        let rules = (0..10)
+            .map(|outcome| {
+                let mut rule = Rule::new(outcome);
+                rule.insert("npc_id", FloatEvaluator::gte(0.));
+                rule
+            })
+            .collect();
+
+        let ruleset = Ruleset::new(rules);
+
+        let queries: Vec<_> = (0..200)
+            .map(|npc_id| {
+                let mut query = Query::new();
+                query.insert("npc_id", npc_id as f64);
+                query
+            })
+            .collect();
+        let queries: Vec<_> = queries.iter().collect();
+
+        let outcomes = |seed| -> Vec<_> {
+            ruleset
+                .evaluate_batch(&queries, seed)
+                .into_iter()
+                .map(|rule| rule.unwrap().outcome)
+                .collect()
+        };
+
+        let first = outcomes(42);
+        assert_eq!(first.len(), 200);
+        assert_eq!(first, outcomes(42));
+        assert_ne!(first, outcomes(43));
+
+        // Batches with adjacent seeds aren't shifted copies of each other
+        assert_ne!(first[1..], outcomes(43)[..199]);
+
+        // The generator is portable, so the seed always selects the same rules
+        assert_eq!(first[..8], [5, 5, 6, 2, 4, 4, 2, 5]);
+    }
+
+    #[test]
+    fn ruleset_positions() {
+        let mut ruleset = Ruleset::new(vec![
+            rule("first", &[]),
+            rule("second", &[("enemies_killed", FloatEvaluator::EqualTo(5.))]),
+            rule("third", &[]),
+        ]);
+
+        let positions = |ruleset: &TestRuleset| -> Vec<_> {
+            (0..ruleset.rules().len())
+                .map(|index| (ruleset.rules()[index].outcome, ruleset.position(index)))
+                .collect()
+        };
+
+        // Rules with the same specificity keep their original order
+        assert_eq!(
+            positions(&ruleset),
+            [("second", 1), ("first", 0), ("third", 2)]
+        );
+
+        // Appended rules are positioned after the existing rules
+        ruleset.append(&mut Ruleset::new(vec![
+            rule("fourth", &[]),
+            rule("fifth", &[("doors_opened", FloatEvaluator::gt(2.))]),
+        ]));
+
+        assert_eq!(
+            positions(&ruleset),
+            [
+                ("second", 1),
+                ("fifth", 4),
+                ("first", 0),
+                ("third", 2),
+                ("fourth", 3)
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn ruleset_serialization() {
+        let mut rule = Rule::new("You killed 5 enemies!".to_string());
+        rule.insert("enemies_killed".to_string(), FloatEvaluator::EqualTo(5.));
+
+        let mut more_specific_rule =
+            Rule::new("You killed 5 enemies and opened 2 doors!".to_string());
+        more_specific_rule.insert("enemies_killed".to_string(), FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened".to_string(), FloatEvaluator::gt(2.));
+
+        let ruleset = Ruleset::new(vec![rule, more_specific_rule]);
+
+        let json = serde_json::to_string(&ruleset).unwrap();
+        assert!(json.starts_with(r#"[{"criteria":{"enemies_killed":{"eq":5.0},"doors_opened""#));
+
+        let ruleset: Ruleset<String, f64, FloatEvaluator, String> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&ruleset).unwrap(), json);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn ruleset_deserialization_sorts_rules() {
+        let ruleset: Ruleset<&str, f64, FloatEvaluator, &str> = serde_json::from_str(
+            r#"[
+                {"criteria": {"enemies_killed": {"eq": 5}}, "outcome": "less specific"},
+                {"criteria": {"enemies_killed": {"eq": 5}, "doors_opened": {"gt": 2}}, "outcome": "more specific"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(ruleset.rules()[0].outcome, "more specific");
+    }
+
+    #[test]
+    #[cfg(feature = "binary")]
+    fn ruleset_binary_shared_criteria() {
+        use crate::{
+            binary::{self, BinaryError, Kind},
+            cache::UNSHARED,
+        };
+
+        type Loaded<'a> = Ruleset<&'a str, f64, FloatEvaluator, &'a str>;
+
+        let mut rule = Rule::new("You killed 5 enemies!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+
+        let mut more_specific_rule = Rule::new("You killed 5 enemies and opened 2 doors!");
+        more_specific_rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+        more_specific_rule.insert("doors_opened", FloatEvaluator::gt(2.));
+
+        let ruleset = Ruleset::with_shared_criteria(vec![rule, more_specific_rule]);
+        assert_eq!(ruleset.slots, [0, UNSHARED, 0]);
+
+        // Shared criteria are loaded rather than detected again
+        let bytes = ruleset.to_bytes().unwrap();
+        let loaded = Loaded::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.slots, ruleset.slots);
+
+        // Slots that don't match the rules' criteria are rejected
+        let rules: Vec<_> = ruleset
+            .rules()
+            .iter()
+            .map(|rule| (&rule.name, &rule.evaluators, &rule.outcome))
+            .collect();
+
+        for slots in [vec![0, 0], vec![0, 3, 0]] {
+            let bytes = binary::to_bytes(Kind::Ruleset, &(&rules, slots, vec![1, 0])).unwrap();
+            assert!(matches!(
+                Loaded::from_bytes(&bytes),
+                Err(BinaryError::Corrupt)
+            ));
+        }
+
+        // So are positions that don't match the rules
+        for positions in [vec![0], vec![1, 1], vec![0, 2]] {
+            let bytes =
+                binary::to_bytes(Kind::Ruleset, &(&rules, &ruleset.slots, positions)).unwrap();
+            assert!(matches!(
+                Loaded::from_bytes(&bytes),
+                Err(BinaryError::Corrupt)
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "binary")]
+    fn ruleset_binary_positions() {
+        let ruleset = Ruleset::new(vec![
+            rule("first", &[]),
+            rule("second", &[("enemies_killed", FloatEvaluator::EqualTo(5.))]),
+        ]);
+
+        let bytes = ruleset.to_bytes().unwrap();
+        let loaded = Ruleset::<&str, f64, FloatEvaluator, &str>::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.positions, [1, 0]);
+    }
 }
@@ -0,0 +1,526 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use indexmap::IndexMap;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{evaluator::Evaluator, hash::FactHasher, query::Query, ruleset::Ruleset};
+
+/// A named test case for a ruleset: a set of facts, and the outcome (or
+/// outcomes) that a query containing those facts is expected to produce.
+///
+/// Scenarios allow the behaviour of a ruleset to be locked in without writing
+/// Rust tests (e.g. by content authors). When the `serde` feature is enabled,
+/// scenarios are (de)serialized as a map with a `name`, a map of `facts`, an
+/// optional `seed` and either an expected `outcome` or a list of expected
+/// `matches` (a `seed` can only be used with an `outcome`):
+///
+/// ```json
+/// [
+///   { "name": "low health", "facts": { "health": 0.2 }, "outcome": "Ouch!" },
+///   { "name": "tied", "facts": { "health": 0.7 }, "matches": ["Hello!", "Hi!"] }
+/// ]
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "RawScenario<FactKey, FactType, Outcome>")
+)]
+pub struct Scenario<FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    /// The name of the scenario (used when reporting failures).
+    pub name: String,
+    /// The facts in the scenario's query.
+    pub facts: IndexMap<FactKey, FactType, FactHasher>,
+    /// The seed used to break ties between equally specific rules when an
+    /// outcome is expected (see `Expectation::Outcome`), which is ignored
+    /// when matches are expected.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub seed: Option<u64>,
+    /// The expected result of evaluating the scenario's query.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub expected: Expectation<Outcome>,
+}
+
+/// The deserialized representation of a `Scenario`, which is validated before
+/// it's converted.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "Scenario")]
+struct RawScenario<FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    name: String,
+    facts: IndexMap<FactKey, FactType, FactHasher>,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(flatten)]
+    expected: Expectation<Outcome>,
+}
+
+#[cfg(feature = "serde")]
+impl<FactKey, FactType, Outcome> TryFrom<RawScenario<FactKey, FactType, Outcome>>
+    for Scenario<FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    type Error = String;
+
+    fn try_from(scenario: RawScenario<FactKey, FactType, Outcome>) -> Result<Self, Self::Error> {
+        // A seed only affects which of the tied rules is selected, so it can't
+        // change the most specific matches
+        if scenario.seed.is_some() && matches!(scenario.expected, Expectation::Matches(_)) {
+            return Err(alloc::format!(
+                "scenario {:?} has a `seed`, which can only be used with an `outcome`",
+                scenario.name
+            ));
+        }
+
+        Ok(Self {
+            name: scenario.name,
+            facts: scenario.facts,
+            seed: scenario.seed,
+            expected: scenario.expected,
+        })
+    }
+}
+
+/// The expected result of evaluating a `Scenario`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Expectation<Outcome> {
+    /// The query is expected to produce the outcome.
+    ///
+    /// If the scenario has a seed, the outcome of the rule selected by
+    /// `Ruleset::evaluate_with_rng` (using a `ChaCha8Rng` seeded with
+    /// `SeedableRng::seed_from_u64`, which selects the same rule on every
+    /// platform) is checked. Otherwise, every one of the most specific matching
+    /// rules must have the outcome (so that the result doesn't depend on
+    /// how ties are broken).
+    Outcome(Outcome),
+    /// The most specific rules that match the query (as returned by
+    /// `Ruleset::evaluate_all`) are expected to have exactly these outcomes,
+    /// in any order. An empty list expects that no rules match.
+    Matches(Vec<Outcome>),
+}
+
+/// A scenario that failed when run against a ruleset by `run_scenarios`.
+pub struct ScenarioFailure<'a, FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    /// The index of the scenario in the scenarios passed to `run_scenarios`.
+    pub index: usize,
+    /// The scenario that failed.
+    pub scenario: &'a Scenario<FactKey, FactType, Outcome>,
+    /// The outcomes that were found: the outcome of the selected rule (if the
+    /// scenario has a seed and expects an outcome), or the outcomes of the most
+    /// specific matching rules.
+    pub found: Vec<&'a Outcome>,
+}
+
+impl<'a, FactKey, FactType, Outcome> ScenarioFailure<'a, FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    /// Returns a value that describes why the scenario failed when displayed
+    /// (e.g. `expected "Ouch!", found nothing`), formatting each outcome with
+    /// the provided function.
+    ///
+    /// Unlike the `Display` implementation of `ScenarioFailure` (which
+    /// formats outcomes with `Debug`), the description doesn't include the
+    /// scenario's name.
+    pub fn display_with<F>(
+        &self,
+        outcome: F,
+    ) -> FailureDisplay<'_, 'a, FactKey, FactType, Outcome, F>
+    where
+        F: Fn(&Outcome, &mut fmt::Formatter<'_>) -> fmt::Result,
+    {
+        FailureDisplay {
+            failure: self,
+            outcome,
+        }
+    }
+}
+
+impl<FactKey, FactType, Outcome> fmt::Display for ScenarioFailure<'_, FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq,
+    Outcome: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scenario {:?}: {}",
+            self.scenario.name,
+            self.display_with(|outcome, f| write!(f, "{:?}", outcome))
+        )
+    }
+}
+
+/// Describes why a scenario failed, as returned by
+/// `ScenarioFailure::display_with`.
+pub struct FailureDisplay<'f, 'a, FactKey, FactType, Outcome, F>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    failure: &'f ScenarioFailure<'a, FactKey, FactType, Outcome>,
+    outcome: F,
+}
+
+impl<FactKey, FactType, Outcome, F> FailureDisplay<'_, '_, FactKey, FactType, Outcome, F>
+where
+    FactKey: core::hash::Hash + Eq,
+    F: Fn(&Outcome, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    /// Writes a comma-separated list of outcomes (or "nothing" if the list is
+    /// empty).
+    fn list<'o>(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        outcomes: impl IntoIterator<Item = &'o Outcome>,
+    ) -> fmt::Result
+    where
+        Outcome: 'o,
+    {
+        let mut empty = true;
+
+        for outcome in outcomes {
+            if !empty {
+                write!(f, ", ")?;
+            }
+
+            (self.outcome)(outcome, f)?;
+            empty = false;
+        }
+
+        if empty {
+            write!(f, "nothing")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<FactKey, FactType, Outcome, F> fmt::Display
+    for FailureDisplay<'_, '_, FactKey, FactType, Outcome, F>
+where
+    FactKey: core::hash::Hash + Eq,
+    F: Fn(&Outcome, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (scenario, found) = (self.failure.scenario, &self.failure.found);
+
+        match (&scenario.expected, scenario.seed) {
+            (Expectation::Outcome(outcome), Some(seed)) => {
+                write!(f, "expected ")?;
+                (self.outcome)(outcome, f)?;
+
+                match found.first() {
+                    Some(found) => {
+                        write!(f, " (with seed {}), found ", seed)?;
+                        (self.outcome)(found, f)
+                    },
+                    None => write!(f, ", but no rules matched"),
+                }
+            },
+            (Expectation::Outcome(outcome), None) => {
+                write!(f, "expected ")?;
+                (self.outcome)(outcome, f)?;
+                write!(f, ", but the most specific matches were ")?;
+                self.list(f, found.iter().copied())
+            },
+            (Expectation::Matches(outcomes), _) => {
+                write!(f, "expected the most specific matches to be ")?;
+                self.list(f, outcomes)?;
+                write!(f, ", found ")?;
+                self.list(f, found.iter().copied())
+            },
+        }
+    }
+}
+
+impl<FactKey, FactType, Outcome> Scenario<FactKey, FactType, Outcome>
+where
+    FactKey: core::hash::Hash + Eq + Clone,
+    FactType: Copy,
+    Outcome: PartialEq,
+{
+    /// Runs the scenario against the provided ruleset, returning the outcomes
+    /// that were found if they don't match the scenario's expectation (see
+    /// `ScenarioFailure::found`).
+    fn run<'a, FactEvaluator>(
+        &self,
+        ruleset: &'a Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    ) -> Result<(), Vec<&'a Outcome>>
+    where
+        FactEvaluator: Evaluator<FactType> + Copy,
+    {
+        let mut query = Query::with_capacity(self.facts.len());
+
+        for (fact, value) in &self.facts {
+            query.insert(fact.clone(), *value);
+        }
+
+        let matched: Vec<_> = ruleset
+            .evaluate_all(&query)
+            .into_iter()
+            .map(|rule| &rule.outcome)
+            .collect();
+
+        match (&self.expected, self.seed) {
+            (Expectation::Outcome(outcome), Some(seed)) => {
+                let selected = ruleset
+                    .evaluate_with_rng(&query, &mut ChaCha8Rng::seed_from_u64(seed))
+                    .map(|rule| &rule.outcome);
+
+                match selected {
+                    Some(selected) if selected == outcome => Ok(()),
+                    selected => Err(selected.into_iter().collect()),
+                }
+            },
+            (Expectation::Outcome(outcome), None) => {
+                if !matched.is_empty() && matched.iter().all(|x| *x == outcome) {
+                    Ok(())
+                } else {
+                    Err(matched)
+                }
+            },
+            (Expectation::Matches(outcomes), _) => {
+                // Compare the outcomes as multisets (ignoring their order)
+                let mut remaining: Vec<_> = matched.clone();
+
+                for outcome in outcomes {
+                    match remaining.iter().position(|x| *x == outcome) {
+                        Some(index) => {
+                            remaining.swap_remove(index);
+                        },
+                        None => return Err(matched),
+                    }
+                }
+
+                if remaining.is_empty() {
+                    Ok(())
+                } else {
+                    Err(matched)
+                }
+            },
+        }
+    }
+}
+
+/// Runs each of the provided scenarios against the ruleset, returning the
+/// scenarios that failed (in the order they were provided, along with their
+/// index in `scenarios`).
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut rule = Rule::new("Ouch!");
+/// rule.insert("health", FloatEvaluator::lt(0.5));
+///
+/// let ruleset = Ruleset::new(vec![rule]);
+///
+/// let mut scenario = Scenario {
+///     name: "low health".to_string(),
+///     facts: Default::default(),
+///     seed: None,
+///     expected: Expectation::Outcome("Ouch!"),
+/// };
+/// scenario.facts.insert("health", 0.25);
+///
+/// assert!(run_scenarios(&ruleset, &[scenario]).is_empty());
+/// # }
+/// ```
+pub fn run_scenarios<'a, FactKey, FactType, FactEvaluator, Outcome>(
+    ruleset: &'a Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    scenarios: &'a [Scenario<FactKey, FactType, Outcome>],
+) -> Vec<ScenarioFailure<'a, FactKey, FactType, Outcome>>
+where
+    FactKey: core::hash::Hash + Eq + Clone,
+    FactType: Copy,
+    FactEvaluator: Evaluator<FactType> + Copy,
+    Outcome: PartialEq,
+{
+    scenarios
+        .iter()
+        .enumerate()
+        .filter_map(|(index, scenario)| {
+            scenario.run(ruleset).err().map(|found| ScenarioFailure {
+                index,
+                scenario,
+                found,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use crate::{prelude::*, testing::dialogue};
+
+    fn scenario(
+        name: &str,
+        facts: &[(&'static str, f64)],
+        seed: Option<u64>,
+        expected: Expectation<&'static str>,
+    ) -> Scenario<&'static str, f64, &'static str> {
+        Scenario {
+            name: name.to_string(),
+            facts: facts.iter().copied().collect(),
+            seed,
+            expected,
+        }
+    }
+
+    #[test]
+    fn scenarios() {
+        let ruleset = dialogue();
+        let low_health = [("concept", 1.), ("health", 0.1)];
+
+        let scenarios = [
+            scenario(
+                "greeting",
+                &[("concept", 1.)],
+                None,
+                Expectation::Outcome("Hello!"),
+            ),
+            scenario(
+                "hurt",
+                &[("concept", 1.), ("health", 0.4)],
+                None,
+                Expectation::Outcome("Ouch!"),
+            ),
+            scenario(
+                "tied",
+                &low_health,
+                None,
+                Expectation::Matches(vec!["Argh!", "Ouch!"]),
+            ),
+            scenario("nothing", &[], None, Expectation::Matches(vec![])),
+            // Ties can't be expected to produce an outcome without a seed
+            scenario("unseeded", &low_health, None, Expectation::Outcome("Ouch!")),
+            scenario(
+                "wrong matches",
+                &low_health,
+                None,
+                Expectation::Matches(vec!["Ouch!"]),
+            ),
+            scenario(
+                "no match",
+                &[("concept", 2.)],
+                Some(0),
+                Expectation::Outcome("Hello!"),
+            ),
+        ];
+
+        let failures = run_scenarios(&ruleset, &scenarios);
+        let names: Vec<_> = failures
+            .iter()
+            .map(|failure| failure.scenario.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["unseeded", "wrong matches", "no match"]);
+        assert_eq!(
+            failures
+                .iter()
+                .map(|failure| failure.index)
+                .collect::<Vec<_>>(),
+            [4, 5, 6]
+        );
+        assert_eq!(
+            failures[1].to_string(),
+            r#"scenario "wrong matches": expected the most specific matches to be "Ouch!", found "Ouch!", "Argh!""#
+        );
+        assert_eq!(
+            failures[2].to_string(),
+            r#"scenario "no match": expected "Hello!", but no rules matched"#
+        );
+    }
+
+    #[test]
+    fn seeded_scenarios() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let ruleset = dialogue();
+        let low_health = [("concept", 1.), ("health", 0.1)];
+
+        let mut query = Query::new();
+        for (fact, value) in low_health {
+            query.insert(fact, value);
+        }
+
+        // The seeded scenario expects whichever outcome the seed selects
+        let selected = ruleset
+            .evaluate_with_rng(&query, &mut ChaCha8Rng::seed_from_u64(7))
+            .unwrap()
+            .outcome;
+
+        // The generator is portable, so the seed always selects the same rule
+        assert_eq!(selected, "Ouch!");
+
+        let scenarios = [
+            scenario(
+                "selected",
+                &low_health,
+                Some(7),
+                Expectation::Outcome(selected),
+            ),
+            scenario("other", &low_health, Some(7), Expectation::Outcome("Argh!")),
+        ];
+
+        let failures = run_scenarios(&ruleset, &scenarios);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].scenario.name, "other");
+        assert_eq!(failures[0].found, [&selected]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn scenario_serialization() {
+        let json = r#"[
+            {"name": "hurt", "facts": {"concept": 1, "health": 0.4}, "outcome": "Ouch!"},
+            {"name": "tied", "facts": {"concept": 1, "health": 0.1}, "matches": ["Ouch!", "Argh!"]},
+            {"name": "seeded", "facts": {"concept": 1, "health": 0.1}, "seed": 3, "outcome": "Argh!"}
+        ]"#;
+
+        let scenarios: Vec<Scenario<String, f64, String>> = serde_json::from_str(json).unwrap();
+        assert_eq!(scenarios[0].expected, Expectation::Outcome("Ouch!".into()));
+        assert_eq!(
+            scenarios[1].expected,
+            Expectation::Matches(vec!["Ouch!".into(), "Argh!".into()])
+        );
+        assert_eq!(scenarios[2].seed, Some(3));
+
+        // A seed doesn't affect the most specific matches
+        let error = serde_json::from_str::<Scenario<String, f64, String>>(
+            r#"{"name": "tied", "facts": {}, "seed": 3, "matches": []}"#,
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().starts_with(
+            r#"scenario "tied" has a `seed`, which can only be used with an `outcome`"#
+        ));
+
+        let json = serde_json::to_string(&scenarios[0]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"hurt","facts":{"concept":1.0,"health":0.4},"outcome":"Ouch!"}"#
+        );
+    }
+}
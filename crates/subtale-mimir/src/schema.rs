@@ -0,0 +1,378 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{evaluator::Evaluator, hash::FactHasher, query::Query, ruleset::Ruleset};
+
+/// The type of a fact's value, as declared in a `FactSchema`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ValueType {
+    /// A numeric value.
+    Number,
+    /// A boolean value.
+    Bool,
+    /// A text value.
+    Text,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number => write!(f, "number"),
+            Self::Bool => write!(f, "bool"),
+            Self::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// A trait for fact values and evaluators that have a `ValueType`, allowing
+/// them to be validated against a `FactSchema`.
+///
+/// For evaluators, the value type is the type of value that the evaluator
+/// compares facts against.
+pub trait Typed {
+    /// Returns the type of the value (or of the values accepted by the
+    /// evaluator).
+    fn value_type(&self) -> ValueType;
+}
+
+impl Typed for f64 {
+    fn value_type(&self) -> ValueType { ValueType::Number }
+}
+
+impl Typed for bool {
+    fn value_type(&self) -> ValueType { ValueType::Bool }
+}
+
+impl Typed for &str {
+    fn value_type(&self) -> ValueType { ValueType::Text }
+}
+
+impl Typed for String {
+    fn value_type(&self) -> ValueType { ValueType::Text }
+}
+
+/// A `FactSchema` declares the fact keys known to your game (along with the
+/// type of each fact's value).
+///
+/// Rulesets (e.g. when they're loaded from a file) and queries can be
+/// validated against a schema to catch mistakes such as a misspelled fact key,
+/// which would otherwise silently make a rule impossible to match.
+///
+/// ```
+/// # #[cfg(feature = "float")] {
+/// use subtale_mimir::prelude::*;
+///
+/// let mut schema = FactSchema::new();
+/// schema.insert("enemies_killed", ValueType::Number);
+///
+/// let mut rule = Rule::new("You killed 5 enemies!");
+/// rule.insert("enemys_killed", FloatEvaluator::EqualTo(5.));
+///
+/// let ruleset = Ruleset::new(vec![rule]);
+/// let errors = schema.validate_ruleset(&ruleset);
+///
+/// assert_eq!(errors[0].key, &"enemys_killed");
+/// assert_eq!(errors[0].kind, SchemaErrorKind::UnknownKey);
+/// # }
+/// ```
+///
+/// When the `serde` feature is enabled, schemas are (de)serialized as a map of
+/// fact keys to value types (e.g. `{"enemies_killed": "number"}`).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct FactSchema<FactKey>
+where
+    FactKey: core::hash::Hash + Eq,
+{
+    facts: IndexMap<FactKey, ValueType, FactHasher>,
+}
+
+impl<FactKey: core::hash::Hash + Eq> FactSchema<FactKey> {
+    /// Instantiates a new, empty schema.
+    pub fn new() -> Self {
+        Self {
+            facts: IndexMap::default(),
+        }
+    }
+
+    /// Declares a fact key (and the type of the fact's value) in the schema,
+    /// replacing any existing declaration of the key.
+    pub fn insert(&mut self, fact: FactKey, value_type: ValueType) {
+        self.facts.insert(fact, value_type);
+    }
+
+    /// Returns the declared type of the provided fact's value (if the fact is
+    /// declared in the schema).
+    pub fn get(&self, fact: &FactKey) -> Option<ValueType> { self.facts.get(fact).copied() }
+
+    /// Returns the number of facts declared in the schema.
+    pub fn len(&self) -> usize { self.facts.len() }
+
+    /// Returns `true` if the schema doesn't declare any facts.
+    pub fn is_empty(&self) -> bool { self.facts.is_empty() }
+
+    /// Returns an iterator over the facts declared in the schema (and their
+    /// value types), in the order they were declared.
+    pub fn iter(&self) -> impl Iterator<Item = (&FactKey, ValueType)> {
+        self.facts
+            .iter()
+            .map(|(fact, value_type)| (fact, *value_type))
+    }
+
+    fn check<'a>(
+        &self,
+        rule: Option<usize>,
+        fact: &'a FactKey,
+        found: ValueType,
+    ) -> Option<SchemaError<'a, FactKey>> {
+        let kind = match self.get(fact) {
+            None => SchemaErrorKind::UnknownKey,
+            Some(expected) if expected != found => {
+                SchemaErrorKind::TypeMismatch { expected, found }
+            },
+            Some(_) => return None,
+        };
+
+        Some(SchemaError {
+            rule,
+            key: fact,
+            kind,
+        })
+    }
+
+    /// Validates the provided ruleset against the schema, returning every
+    /// criterion that refers to an unknown fact key or compares a fact
+    /// against a value of the wrong type.
+    ///
+    /// Errors are returned in the order of the ruleset's rules (see
+    /// `Ruleset::rules`), and refer to each rule by its position in the
+    /// collection the ruleset was created from (see `Ruleset::position`).
+    pub fn validate_ruleset<'a, FactType, FactEvaluator, Outcome>(
+        &self,
+        ruleset: &'a Ruleset<FactKey, FactType, FactEvaluator, Outcome>,
+    ) -> Vec<SchemaError<'a, FactKey>>
+    where
+        FactType: Copy,
+        FactEvaluator: Evaluator<FactType> + Copy + Typed,
+    {
+        ruleset
+            .rules()
+            .iter()
+            .enumerate()
+            .flat_map(|(index, rule)| {
+                rule.evaluators.iter().filter_map(move |(fact, evaluator)| {
+                    self.check(Some(ruleset.position(index)), fact, evaluator.value_type())
+                })
+            })
+            .collect()
+    }
+
+    /// Validates the provided query against the schema, returning every fact
+    /// with an unknown key or a value of the wrong type.
+    pub fn validate_query<'a, FactType>(
+        &self,
+        query: &'a Query<FactKey, FactType>,
+    ) -> Vec<SchemaError<'a, FactKey>>
+    where
+        FactType: Copy + Typed,
+    {
+        query
+            .facts()
+            .iter()
+            .filter_map(|(fact, value)| self.check(None, fact, value.value_type()))
+            .collect()
+    }
+
+    /// Validates the provided query against the schema in debug builds,
+    /// panicking if the query contains any facts that don't match the schema.
+    ///
+    /// This does nothing in release builds (without debug assertions), so it
+    /// can be left in your game's code to catch mistakes during development.
+    #[track_caller]
+    pub fn debug_assert_query<FactType>(&self, query: &Query<FactKey, FactType>)
+    where
+        FactKey: fmt::Debug,
+        FactType: Copy + Typed,
+    {
+        #[cfg(debug_assertions)]
+        {
+            let errors = self.validate_query(query);
+            assert!(
+                errors.is_empty(),
+                "query doesn't match schema: {:?}",
+                errors
+            );
+        }
+
+        #[cfg(not(debug_assertions))]
+        let _ = query;
+    }
+}
+
+/// The reason that a fact (or a criterion) doesn't match a `FactSchema`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaErrorKind {
+    /// The fact key isn't declared in the schema.
+    UnknownKey,
+    /// The fact's value (or the value compared against by the criterion's
+    /// evaluator) has a different type to the one declared in the schema.
+    TypeMismatch {
+        /// The type declared in the schema.
+        expected: ValueType,
+        /// The type of the value (or the evaluator).
+        found: ValueType,
+    },
+}
+
+/// A fact (in a query) or a criterion (in a rule) that doesn't match a
+/// `FactSchema`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SchemaError<'a, FactKey> {
+    /// The position of the rule containing the criterion in the collection the
+    /// ruleset was created from (see `Ruleset::position`), or `None` if the
+    /// error was found in a query.
+    pub rule: Option<usize>,
+    /// The fact key that doesn't match the schema.
+    pub key: &'a FactKey,
+    /// The reason that the fact doesn't match the schema.
+    pub kind: SchemaErrorKind,
+}
+
+impl<FactKey: fmt::Debug> fmt::Display for SchemaError<'_, FactKey> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(rule) = self.rule {
+            write!(f, "rule #{}: ", rule)?;
+        }
+
+        match self.kind {
+            SchemaErrorKind::UnknownKey => write!(f, "unknown fact {:?}", self.key),
+            SchemaErrorKind::TypeMismatch { expected, found } => write!(
+                f,
+                "fact {:?} should be a {} (found {})",
+                self.key, expected, found
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "float")]
+mod tests {
+    use crate::prelude::*;
+
+    fn schema() -> FactSchema<&'static str> {
+        let mut schema = FactSchema::new();
+        schema.insert("enemies_killed", ValueType::Number);
+        schema.insert("doors_opened", ValueType::Number);
+        schema
+    }
+
+    #[test]
+    fn ruleset_validation() {
+        let mut rule = Rule::new("You killed 5 enemies!");
+        rule.insert("enemies_killed", FloatEvaluator::EqualTo(5.));
+
+        let mut misspelled_rule = Rule::new("You opened 2 doors!");
+        misspelled_rule.insert("enemies_killed", FloatEvaluator::gt(0.));
+        misspelled_rule.insert("dors_opened", FloatEvaluator::EqualTo(2.));
+
+        let ruleset = Ruleset::new(vec![rule, misspelled_rule]);
+        let errors = schema().validate_ruleset(&ruleset);
+
+        assert_eq!(
+            errors,
+            [SchemaError {
+                // The misspelled rule is sorted first, but was created second
+                rule: Some(1),
+                key: &"dors_opened",
+                kind: SchemaErrorKind::UnknownKey,
+            }]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            r#"rule #1: unknown fact "dors_opened""#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dsl")]
+    fn type_mismatch() {
+        use crate::dsl;
+
+        let mut schema = FactSchema::new();
+        schema.insert("concept", ValueType::Text);
+        schema.insert("health", ValueType::Number);
+
+        let ruleset =
+            dsl::parse(r#"rule hurt { concept == "Greet"; health == "low"; } => "Ouch""#).unwrap();
+        let errors = schema.validate_ruleset(&ruleset);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            SchemaErrorKind::TypeMismatch {
+                expected: ValueType::Number,
+                found: ValueType::Text,
+            }
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            r#"rule #0: fact "health" should be a number (found text)"#
+        );
+
+        let mut query = Query::new();
+        query.insert("concept", dsl::Value::Text("Greet"));
+        query.insert("health", dsl::Value::Bool(true));
+
+        let errors = schema.validate_query(&query);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].rule, None);
+        assert_eq!(errors[0].key, &"health");
+    }
+
+    #[test]
+    fn query_validation() {
+        let mut query = Query::new();
+        query.insert("enemies_killed", 5.);
+        assert!(schema().validate_query(&query).is_empty());
+        schema().debug_assert_query(&query);
+
+        query.insert("enemys_killed", 5.);
+        assert_eq!(
+            schema().validate_query(&query)[0].kind,
+            SchemaErrorKind::UnknownKey
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "query doesn't match schema")]
+    fn query_debug_assertion() {
+        let mut query = Query::new();
+        query.insert("enemys_killed", 5.);
+        schema().debug_assert_query(&query);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn schema_serialization() {
+        let json = serde_json::to_string(&schema()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"enemies_killed":"number","doors_opened":"number"}"#
+        );
+
+        let schema: FactSchema<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            schema.get(&"doors_opened".to_string()),
+            Some(ValueType::Number)
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::prelude::*;
+
+/// The type of the rulesets used by tests.
+pub(crate) type TestRuleset = Ruleset<&'static str, f64, FloatEvaluator, &'static str>;
+
+/// Creates an unnamed rule with the provided outcome and criteria.
+pub(crate) fn rule(
+    outcome: &'static str,
+    criteria: &[(&'static str, FloatEvaluator)],
+) -> Rule<&'static str, f64, FloatEvaluator, &'static str> {
+    let mut rule = Rule::new(outcome);
+    for (fact, evaluator) in criteria {
+        rule.insert(*fact, *evaluator);
+    }
+    rule
+}
+
+/// Creates a named rule with the provided outcome and criteria.
+pub(crate) fn named(
+    name: &str,
+    outcome: &'static str,
+    criteria: &[(&'static str, FloatEvaluator)],
+) -> Rule<&'static str, f64, FloatEvaluator, &'static str> {
+    let mut rule = rule(outcome, criteria);
+    rule.name = Some(name.into());
+    rule
+}
+
+/// Returns the index of the rule with the provided outcome (in the order of
+/// `Ruleset::rules`).
+pub(crate) fn index(ruleset: &TestRuleset, outcome: &str) -> usize {
+    ruleset
+        .rules()
+        .iter()
+        .position(|rule| rule.outcome == outcome)
+        .unwrap()
+}
+
+/// A small dialogue ruleset: a greeting, two equally specific rules for when
+/// the speaker is hurt (which tie with each other), and an unrelated rule for
+/// when the speaker is tired.
+pub(crate) fn dialogue() -> TestRuleset {
+    Ruleset::new(vec![
+        named(
+            "greet",
+            "Hello!",
+            &[("concept", FloatEvaluator::EqualTo(1.))],
+        ),
+        named(
+            "hurt, badly",
+            "Ouch!",
+            &[
+                ("concept", FloatEvaluator::EqualTo(1.)),
+                ("health", FloatEvaluator::lt(0.5)),
+            ],
+        ),
+        rule(
+            "Argh!",
+            &[
+                ("concept", FloatEvaluator::EqualTo(1.)),
+                ("health", FloatEvaluator::lt(0.25)),
+            ],
+        ),
+        named("tired", "Phew!", &[("stamina", FloatEvaluator::lt(0.5))]),
+    ])
+}